@@ -2,7 +2,11 @@ use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc, sync::mpsc
 
 use gtk::{
     gdk::{self, prelude::*},
-    gio, glib,
+    gio::{
+        self,
+        prelude::{FileExt, FileMonitorExt, ListModelExt},
+    },
+    glib,
     prelude::{GtkApplicationExt, GtkWindowExt, WidgetExt},
 };
 use gtk4_layer_shell::LayerShell;
@@ -10,8 +14,9 @@ use gtk4_layer_shell::LayerShell;
 use crate::{
     cli,
     config::{self, Config},
-    dbus, error, log, monitor,
+    dbus, error, event, log, monitor,
     socket::{self, r#async::UnixStreamWrapper},
+    style,
 };
 
 use super::cli::{Arguments, Command};
@@ -22,48 +27,228 @@ where
     WM: AzaleaAppExt,
     Self: 'static + Sized,
 {
-    config: config::Config<WM::ConfigWrapper>,
+    config: config::Config<WM::ConfigWrapper, WM::WidgetTemplate>,
+    config_path: Option<PathBuf>,
+    /// Config file and every file it includes, as last loaded
+    config_files: Vec<PathBuf>,
+    /// Watched config files, only set in watch mode
+    config_monitors: Vec<gio::FileMonitor>,
+    config_changed: (flume::Sender<()>, flume::Receiver<()>),
+    layout: Option<config::layout::Name>,
     dbus: Option<dbus::DBusWrapper>,
-    windows: HashMap<String, (config::window::Id, WM::WindowWrapper)>,
+    windows: HashMap<String, WindowInstance<WM::WindowWrapper>>,
 
+    default_css_provider: gtk::CssProvider,
     dynamic_css_provider: gtk::CssProvider,
+    style_path: Option<PathBuf>,
+    /// Style file and its imports, only set in watch mode
+    style_monitors: Vec<gio::FileMonitor>,
+}
+
+/// Window created from a window template
+struct WindowInstance<WindowWrapper> {
+    template_id: config::window::Id,
+    /// Connector of the monitor the window is bound to, if any
+    monitor: Option<monitor::Id>,
+    wrapper: WindowWrapper,
 }
 
 impl<WM> AzaleaApp<WM>
 where
     WM: AzaleaAppExt,
 {
-    pub fn new(config: config::Config<WM::ConfigWrapper>) -> Self {
+    pub fn new(config: config::Config<WM::ConfigWrapper, WM::WidgetTemplate>) -> Self {
         Self {
+            layout: config.layout.clone(),
             config,
+            config_path: None,
+            config_files: vec![],
+            config_monitors: vec![],
+            config_changed: flume::unbounded(),
             dbus: dbus::DBusWrapper::new().ok(),
             windows: Default::default(),
 
+            default_css_provider: gtk::CssProvider::new(),
             dynamic_css_provider: gtk::CssProvider::new(),
+            style_path: None,
+            style_monitors: vec![],
         }
     }
 
-    fn load_config(path: &PathBuf) -> Result<Config<WM::ConfigWrapper>, error::ConfigError> {
-        let file = std::fs::File::open(path)?;
-        let reader = std::io::BufReader::new(file);
-        let ext = path
-            .extension()
-            .ok_or(error::ConfigError::MissingExtension)?;
+    /// Loads a config file along with its includes, resolving widget templates
+    ///
+    /// Every file read is added to `files`
+    fn load_config(
+        path: &PathBuf,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Config<WM::ConfigWrapper, WM::WidgetTemplate>, error::ConfigError> {
+        let mut layers = vec![];
+        Self::read_config_files(path, &mut vec![], &mut layers)?;
+        files.extend(layers.iter().map(|layer| layer.path.clone()));
 
-        Ok(if ext == "json" {
-            serde_json::from_reader(reader)
-                .map_err(|e| error::ConfigError::ParsingError(e.to_string()))?
-        } else {
-            ron::de::from_reader(reader)
-                .map_err(|e| error::ConfigError::ParsingError(e.to_string()))?
+        config::template::scope(config::file::templates(&layers), || {
+            let mut config: Config<WM::ConfigWrapper, WM::WidgetTemplate> = layers[0].parse()?;
+            for layer in &layers[1..] {
+                let included = layer.parse().map_err(|e| error::ConfigError::Include {
+                    path: layer.path.clone(),
+                    source: Box::new(e),
+                })?;
+                config.merge(included);
+            }
+            Ok(config)
         })
     }
 
+    /// Reads a config file and its includes, in the order they're merged
+    fn read_config_files(
+        path: &PathBuf,
+        visited: &mut Vec<PathBuf>,
+        layers: &mut Vec<config::file::File>,
+    ) -> Result<(), error::ConfigError> {
+        let canonical = path.canonicalize()?;
+        if visited.contains(&canonical) {
+            return Err(error::ConfigError::Invalid(vec![format!(
+                "{path:?} includes itself"
+            )]));
+        }
+
+        let file = config::file::File::read(path)?;
+        let includes: Vec<PathBuf> = file.includes().collect();
+        layers.push(file);
+
+        visited.push(canonical);
+        for include in includes {
+            Self::read_config_files(&include, visited, layers).map_err(|e| {
+                error::ConfigError::Include {
+                    path: include.clone(),
+                    source: Box::new(e),
+                }
+            })?;
+        }
+        visited.pop();
+
+        Ok(())
+    }
+
+    /// Rewrites a single config file in another format, includes are kept as they are and
+    /// template references are replaced by the templates
+    fn convert_config_file(
+        input: &PathBuf,
+        output: Option<&PathBuf>,
+        format: Option<config::format::Format>,
+    ) -> Result<(), error::ConfigError> {
+        let format = match (format, output) {
+            (Some(format), _) => format,
+            (None, Some(output)) => config::format::Format::from_path(output)?,
+            (None, None) => return Err(error::ConfigError::MissingExtension),
+        };
+
+        let mut layers = vec![];
+        Self::read_config_files(input, &mut vec![], &mut layers)?;
+        let config: Config<WM::ConfigWrapper, WM::WidgetTemplate> =
+            config::template::scope(config::file::templates(&layers), || layers[0].parse())?;
+        let text = format.serialize(&config)?;
+
+        match output {
+            Some(output) => std::fs::write(output, text)?,
+            None => println!("{text}"),
+        }
+
+        Ok(())
+    }
+
+    fn default_config_path() -> PathBuf {
+        gtk::glib::user_config_dir().join(WM::CONFIG_PATH)
+    }
+
+    /// Finds mistakes that deserialization can't catch, monitors are checked separately by
+    /// `validate_monitors` as they need a display
+    fn validate_config(config: &Config<WM::ConfigWrapper, WM::WidgetTemplate>) -> Vec<String> {
+        let mut problems = vec![];
+
+        let mut ids: Vec<&config::window::Id> = config.windows.keys().collect();
+        ids.sort();
+
+        for id in ids {
+            for problem in WM::validate(&config.windows[id].config) {
+                problems.push(format!("Window {id}: {problem}"));
+            }
+        }
+
+        for (name, layout) in &config.layouts {
+            for id in &layout.windows {
+                if !config.windows.contains_key(id) {
+                    problems.push(format!("Layout {name}: unknown window template {id}"));
+                }
+            }
+        }
+
+        if let Some(name) = &config.layout {
+            if !config.layouts.contains_key(name) {
+                problems.push(format!("Unknown default layout {name}"));
+            }
+        }
+
+        problems
+    }
+
+    /// Finds monitor matches that don't match any connected monitor, only run by the daemon
+    fn validate_monitors(config: &Config<WM::ConfigWrapper, WM::WidgetTemplate>) -> Vec<String> {
+        if gtk::init().is_err() {
+            log::warning!("No display available, skipping monitor checks");
+            return vec![];
+        }
+
+        let mut ids: Vec<&config::window::Id> = config.windows.keys().collect();
+        ids.sort();
+
+        let mut problems = vec![];
+        for id in ids {
+            let monitor_matches = match &config.windows[id].monitor {
+                monitor::Monitor::Single(monitor_match) => vec![monitor_match],
+                monitor::Monitor::Multi(monitor_matches) => monitor_matches.iter().collect(),
+                monitor::Monitor::Dynamic | monitor::Monitor::All => vec![],
+            };
+
+            for monitor_match in monitor_matches {
+                if monitor_match.find_matches().is_empty() {
+                    problems.push(format!(
+                        "Window {id}: monitor {monitor_match:?} doesn't match any monitor"
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Loads and validates a config file, printing every problem found
+    fn validate_config_file(path: &PathBuf) -> bool {
+        let config = match Self::load_config(path, &mut vec![]) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("{path:?}: {e}");
+                return false;
+            }
+        };
+
+        let problems = Self::validate_config(&config);
+        for problem in &problems {
+            println!("{path:?}: {problem}");
+        }
+
+        if problems.is_empty() {
+            println!("{path:?}: Ok");
+        }
+
+        problems.is_empty()
+    }
+
     pub fn run(self) {
-        let args = {
+        let mut args = {
             let arg_style = clap::builder::styling::Style::new().bold().underline();
 
-            Arguments::parse(format!(
+            Arguments::<WM::Command>::parse(format!(
                 "{}Window IDs:{} {}",
                 arg_style.render(),
                 arg_style.render_reset(),
@@ -74,6 +259,37 @@ where
             ))
         };
 
+        if let Command::Config(cli::config::Command::Validate { path }) = &args.command {
+            let path = path.clone().unwrap_or(Self::default_config_path());
+            let valid = Self::validate_config_file(&path);
+            std::process::exit(if valid { 0 } else { 1 });
+        }
+
+        // The daemon may run in another directory
+        if let Command::Config(cli::config::Command::Save { path: Some(path) })
+        | Command::Config(cli::config::Command::Reload { file: Some(path) })
+        | Command::Style(cli::style::Command::Reload {
+            file: Some(path), ..
+        }) = &mut args.command
+        {
+            if let Ok(absolute) = std::path::absolute(&*path) {
+                *path = absolute;
+            }
+        }
+
+        if let Command::Config(cli::config::Command::Convert {
+            input,
+            output,
+            format,
+        }) = &args.command
+        {
+            if let Err(e) = Self::convert_config_file(input, output.as_ref(), *format) {
+                println!("Failed to convert {input:?}: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+
         let socket_path = glib::user_runtime_dir().join(WM::SOCKET_NAME);
 
         if let Some(dbus) = &self.dbus {
@@ -90,20 +306,43 @@ where
         }
     }
 
-    fn daemon(mut self, args: Arguments, socket_path: PathBuf) {
-        match args.command {
+    fn daemon(mut self, args: Arguments<WM::Command>, socket_path: PathBuf) {
+        let watch_config = match args.command {
             Command::Daemon(cli::daemon::Command::Start {
                 config: config_path,
+                watch_config,
+                strict,
             }) => {
                 let config_path = config_path
                     .map(|p| PathBuf::from(&p))
-                    .unwrap_or(gtk::glib::user_config_dir().join(WM::CONFIG_PATH));
+                    .unwrap_or(Self::default_config_path());
+
+                let mut files = vec![];
+                let config = Self::load_config(&config_path, &mut files).and_then(|config| {
+                    let mut problems = Self::validate_config(&config);
+                    problems.extend(Self::validate_monitors(&config));
+                    if strict && !problems.is_empty() {
+                        return Err(error::ConfigError::Invalid(problems));
+                    }
+                    for problem in problems {
+                        log::warning!("{}", problem);
+                    }
+                    Ok(config)
+                });
 
-                match Self::load_config(&config_path) {
+                match config {
                     Ok(config) => {
                         log::message!("Config loaded from {:?}", config_path);
                         self.config = config;
                     }
+                    Err(err) if strict => {
+                        log::warning!(
+                            "Refusing to start, config at {:?} is invalid: {}",
+                            config_path,
+                            err
+                        );
+                        std::process::exit(1);
+                    }
                     Err(err) => match err {
                         error::ConfigError::Io(_) => {
                             log::message!(
@@ -118,19 +357,36 @@ where
                         ),
                     },
                 }
+
+                self.config_path = Some(config_path);
+                self.config_files = files;
+
+                self.layout = match Self::load_layout_state() {
+                    Some(name) if self.config.layouts.contains_key(&name) => Some(name),
+                    _ => self.config.layout.clone(),
+                };
+
+                watch_config
             }
             Command::Monitors => {
                 println!("{}", monitor::monitors_to_string());
                 return;
             }
-            Command::Config(cli::config::Command::View { json }) => {
-                println!("{}", self.config_to_string(json));
+            Command::Config(cli::config::Command::View { json, format }) => {
+                match self.config_to_string(json, format) {
+                    Ok(text) => println!("{text}"),
+                    Err(e) => log::warning!("{}", e),
+                }
+                return;
+            }
+            Command::Config(cli::config::Command::Schema) => {
+                println!("{}", Self::config_schema());
                 return;
             }
             _ => {
                 log::error!("Daemon isn't running, invalid command: {:?}", args.command);
             }
-        }
+        };
 
         let app = gtk::Application::builder()
             .application_id(WM::APP_ID)
@@ -159,7 +415,17 @@ where
 
             state.borrow_mut().create_all_windows(app);
 
-            Self::load_style(&gtk::CssProvider::new(), None);
+            Self::watch_monitors(&state, app);
+
+            if watch_config {
+                Self::watch_config(&state, app);
+            }
+
+            WM::activate(app);
+
+            Self::load_default_style(&state.borrow().default_css_provider);
+
+            Self::listen_style_changes(&state);
 
             match socket::r#async::UnixListenerWrapper::bind(&socket_path) {
                 Ok(listener) => {
@@ -170,20 +436,17 @@ where
                         state,
                         async move {
                             listener
-                                .loop_accept(async |mut stream: UnixStreamWrapper| {
-                                    match stream.read().await {
-                                        Ok(cmd) => {
-                                            let answer =
-                                                state.borrow_mut().handle_command(cmd, &app);
-                                            drop(stream.write(answer).await);
-                                            return true;
-                                        }
-                                        Err(e) => {
-                                            let answer = cli::Response::Error(format!("{e:?}"));
-                                            drop(stream.write(answer).await);
-                                            return false;
+                                .loop_accept(async |stream: UnixStreamWrapper| {
+                                    glib::spawn_future_local(glib::clone!(
+                                        #[weak]
+                                        app,
+                                        #[weak]
+                                        state,
+                                        async move {
+                                            Self::handle_connection(stream, &state, &app).await;
                                         }
-                                    };
+                                    ));
+                                    return true;
                                 })
                                 .await;
                         }
@@ -202,15 +465,38 @@ where
         drop(pong_rx.try_recv());
     }
 
-    fn remote(self, args: Arguments, socket_path: PathBuf, retry: Option<std::time::Duration>) {
+    fn remote(
+        self,
+        args: Arguments<WM::Command>,
+        socket_path: PathBuf,
+        retry: Option<std::time::Duration>,
+    ) {
         loop {
             match socket::sync::UnixStreamWrapper::connect(&socket_path) {
                 Ok(mut stream) => {
-                    if let Err(e) = stream.write(&args.command) {
+                    match stream.handshake() {
+                        Ok(_) => {}
+                        Err(socket::Error::Incompatible { local, remote }) => {
+                            log::warning!(
+                                "Daemon uses protocol version {remote}, but this client uses version {local}. Restart the daemon to update it."
+                            );
+                            return;
+                        }
+                        Err(e) => {
+                            log::warning!("Handshake with daemon failed: {e:?}");
+                            return;
+                        }
+                    }
+
+                    if let Err(e) = stream.send(&args.command) {
                         log::warning!("failed to write {e:?}");
+                    } else if let Command::Subscribe { .. } = args.command {
+                        for line in stream.lines() {
+                            println!("{line}");
+                        }
                     } else {
                         match stream.read::<cli::Response>() {
-                            Ok(response) => match response {
+                            Ok(frame) => match frame.payload {
                                 cli::Response::Success(ans) => println!("{ans}"),
                                 cli::Response::Error(e) => log::warning!("{e:?}"),
                             },
@@ -231,32 +517,214 @@ where
         }
     }
 
-    fn handle_command(&mut self, cmd: Command, app: &gtk::Application) -> cli::Response {
+    /// Finds the uuids of the windows matching the selector
+    fn select_windows(&self, selector: &cli::window::Selector) -> Result<Vec<String>, String> {
+        if let Some(uuid) = &selector.uuid {
+            if !selector.monitor.is_empty() {
+                return Err(format!(
+                    "Monitor filters can't be combined with a window uuid"
+                ));
+            }
+            if !self.windows.contains_key(uuid) {
+                return Err(format!("Window with id {} not found", uuid));
+            }
+            return Ok(vec![uuid.clone()]);
+        }
+
+        let Some(template) = &selector.template else {
+            return Err(format!("Missing window uuid or template id"));
+        };
+
+        let connectors: Vec<monitor::Id> = selector
+            .monitor
+            .find_matches()
+            .iter()
+            .map(monitor::id)
+            .collect();
+
+        let uuids: Vec<String> = self
+            .windows
+            .iter()
+            .filter(|(_, instance)| instance.template_id == *template)
+            .filter(|(_, instance)| {
+                selector.monitor.is_empty()
+                    || instance
+                        .monitor
+                        .as_ref()
+                        .is_some_and(|connector| connectors.contains(connector))
+            })
+            .map(|(uuid, _)| uuid.clone())
+            .collect();
+
+        if uuids.is_empty() {
+            return Err(format!("No window found for template {}", template));
+        }
+
+        Ok(uuids)
+    }
+
+    fn set_windows_visible(
+        &self,
+        selector: &cli::window::Selector,
+        visible: bool,
+    ) -> cli::Response {
+        let uuids = match self.select_windows(selector) {
+            Ok(uuids) => uuids,
+            Err(e) => return cli::Response::Error(e),
+        };
+        for uuid in uuids {
+            if let Some(instance) = self.windows.get(&uuid) {
+                WM::unwrap_window(&instance.wrapper).set_visible(visible);
+            }
+        }
+        cli::Response::Success(format!("Ok"))
+    }
+
+    /// Serves requests from a single client until it disconnects
+    async fn handle_connection(
+        mut stream: UnixStreamWrapper,
+        state: &Rc<RefCell<Self>>,
+        app: &gtk::Application,
+    ) {
+        if let Err(e) = stream.accept_handshake().await {
+            log::warning!("Client handshake failed: {e:?}");
+            return;
+        }
+
+        loop {
+            match stream.read::<Command<WM::Command>>().await {
+                Ok(socket::Frame {
+                    payload: Command::Subscribe { topics },
+                    ..
+                }) => {
+                    // Subscribers don't send anything else, so they're dropped as soon as
+                    // they disconnect instead of on the next event
+                    let closed = stream.closed();
+                    let events = event::subscribe(topics);
+                    let forward = async {
+                        while let Ok(event) = events.recv_async().await {
+                            if stream.write_line(&event).await.is_err() {
+                                break;
+                            }
+                        }
+                    };
+                    futures_lite::future::or(forward, closed).await;
+                    return;
+                }
+                Ok(socket::Frame { id, payload }) => {
+                    let answer = state.borrow_mut().handle_command(payload, app);
+                    if stream.write(id, answer).await.is_err() {
+                        return;
+                    }
+                }
+                Err(socket::Error::Closed) => return,
+                Err(e) => {
+                    let answer = cli::Response::Error(format!("{e:?}"));
+                    drop(stream.write(0, answer).await);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handle_command(
+        &mut self,
+        cmd: Command<WM::Command>,
+        app: &gtk::Application,
+    ) -> cli::Response {
         match cmd {
-            Command::Daemon(cli::daemon::Command::Start { config: _ }) => {
+            Command::Daemon(cli::daemon::Command::Start { .. }) => {
                 return cli::Response::Error(format!("There's already an instance running."));
             }
             Command::Daemon(cli::daemon::Command::Stop) => app.quit(),
             Command::Window(window_cmd) => match window_cmd {
-                cli::window::Command::Create(arg) => self.create_window(&arg.id, None, app),
+                cli::window::Command::Create(arg) => {
+                    if !self.config.windows.contains_key(&arg.id) {
+                        return cli::Response::Error(format!(
+                            "Window configuration not found for id {}",
+                            arg.id
+                        ));
+                    }
+
+                    if arg.monitor.is_empty() {
+                        self.create_window(&arg.id, None, app);
+                    } else {
+                        let monitors = arg.monitor.find_matches();
+                        if monitors.is_empty() {
+                            return cli::Response::Error(format!(
+                                "No monitor matches {:?}",
+                                arg.monitor
+                            ));
+                        }
+                        for monitor in monitors {
+                            self.create_window(&arg.id, Some(monitor), app);
+                        }
+                    }
+                }
                 cli::window::Command::Toggle(arg) => {
-                    let Some((_, wrapper)) = self.windows.get(&arg.uuid) else {
+                    let Some(instance) = self.windows.get(&arg.uuid) else {
                         return cli::Response::Error(format!(
                             "Window with id {} not found",
                             arg.uuid
                         ));
                     };
-                    let window = WM::unwrap_window(wrapper);
+                    let window = WM::unwrap_window(&instance.wrapper);
                     window.set_visible(!window.get_visible());
                 }
+                cli::window::Command::Close(selector) => {
+                    let uuids = match self.select_windows(&selector) {
+                        Ok(uuids) => uuids,
+                        Err(e) => return cli::Response::Error(e),
+                    };
+                    for uuid in uuids {
+                        if let Some(instance) = self.windows.remove(&uuid) {
+                            Self::destroy_window(&uuid, &instance);
+                        }
+                    }
+                }
+                cli::window::Command::Show(selector) => {
+                    return self.set_windows_visible(&selector, true);
+                }
+                cli::window::Command::Hide(selector) => {
+                    return self.set_windows_visible(&selector, false);
+                }
+                cli::window::Command::SetMonitor(arg) => {
+                    let Some(monitor) = monitor::monitors()
+                        .into_iter()
+                        .find(|monitor| monitor::id(monitor) == arg.to)
+                    else {
+                        return cli::Response::Error(format!("Monitor {} not found", arg.to));
+                    };
+                    let uuids = match self.select_windows(&arg.selector) {
+                        Ok(uuids) => uuids,
+                        Err(e) => return cli::Response::Error(e),
+                    };
+                    // Only layer shell windows can be placed on a monitor
+                    if let Some(uuid) = uuids.iter().find(|uuid| {
+                        self.windows.get(*uuid).is_some_and(|instance| {
+                            !WM::unwrap_window(&instance.wrapper).is_layer_window()
+                        })
+                    }) {
+                        return cli::Response::Error(format!(
+                            "Window {uuid} isn't a layer shell window"
+                        ));
+                    }
+                    for uuid in uuids {
+                        if let Some(instance) = self.windows.get_mut(&uuid) {
+                            WM::unwrap_window(&instance.wrapper).set_monitor(Some(&monitor));
+                            instance.monitor = Some(arg.to.clone());
+                        }
+                    }
+                }
                 cli::window::Command::Uuid => {
                     let uuids: Vec<HashMap<&str, String>> = self
                         .windows
                         .iter()
-                        .map(|(k, (template_id, window))| {
-                            let window = WM::unwrap_window(window);
+                        .map(|(k, instance)| {
+                            let window = WM::unwrap_window(&instance.wrapper);
                             HashMap::from([
                                 ("uuid", k.clone()),
+                                ("connector", instance.monitor.clone().unwrap_or_default()),
                                 (
                                     "monitor",
                                     window
@@ -272,7 +740,7 @@ where
                                         .map(|m| m.to_string())
                                         .unwrap_or(String::new()),
                                 ),
-                                ("template", template_id.clone()),
+                                ("template", instance.template_id.clone()),
                             ])
                         })
                         .collect();
@@ -285,46 +753,194 @@ where
             Command::Layer(cli::layer_shell::Command::Toggle(arg)) => self
                 .windows
                 .values()
-                .map(|(_, win)| WM::unwrap_window(win))
+                .map(|instance| WM::unwrap_window(&instance.wrapper))
                 .filter(|win| arg.cmp(win))
                 .for_each(|win| win.set_visible(!win.get_visible())),
-            Command::Config(cli::config::Command::View { json }) => {
-                return cli::Response::Success(self.config_to_string(json));
+            Command::Layer(cli::layer_shell::Command::Set(arg)) => {
+                let windows: Vec<&gtk::Window> = self
+                    .windows
+                    .values()
+                    .map(|instance| WM::unwrap_window(&instance.wrapper))
+                    .filter(|win| arg.filter.cmp(win))
+                    .collect();
+
+                if windows.is_empty() {
+                    return cli::Response::Error(format!(
+                        "No window matches namespace {}",
+                        arg.filter.namespace
+                    ));
+                }
+
+                for window in &windows {
+                    arg.apply(window);
+                }
+
+                return cli::Response::Success(format!("Updated {} window(s)", windows.len()));
+            }
+            Command::Config(cli::config::Command::View { json, format }) => {
+                return match self.config_to_string(json, format) {
+                    Ok(text) => cli::Response::Success(text),
+                    Err(e) => cli::Response::Error(e.to_string()),
+                };
+            }
+            Command::Config(cli::config::Command::Reload { file }) => {
+                return self.reload_config(file, app);
+            }
+            Command::Config(cli::config::Command::Save { path }) => {
+                return self.save_config(path);
+            }
+            Command::Config(cli::config::Command::Schema) => {
+                return cli::Response::Success(Self::config_schema());
+            }
+            Command::Config(
+                cli::config::Command::Validate { .. } | cli::config::Command::Convert { .. },
+            ) => {
+                return cli::Response::Error(format!("Command runs on the client"));
             }
             Command::Monitors => {
                 return cli::Response::Success(monitor::monitors_to_string());
             }
+            Command::Layout(cli::layout::Command::Switch { name }) => {
+                return self.switch_layout(name, app);
+            }
+            Command::Layout(cli::layout::Command::List) => {
+                let mut names: Vec<String> = self
+                    .config
+                    .layouts
+                    .keys()
+                    .map(|name| {
+                        if self.layout.as_ref() == Some(name) {
+                            format!("{name} (active)")
+                        } else {
+                            name.clone()
+                        }
+                    })
+                    .collect();
+                names.sort();
+                return cli::Response::Success(names.join("\n"));
+            }
             Command::Style(command) => match command {
-                cli::style::Command::Reload { file } => {
+                cli::style::Command::Reload { file, watch } => {
                     let file = file.unwrap_or(glib::user_config_dir().join(WM::STYLE_PATH));
 
-                    let Ok(scss) = std::fs::read_to_string(&file) else {
-                        return cli::Response::Error(format!(
-                            "failed to find style file: {file:?}"
-                        ));
-                    };
-
-                    Self::load_style(&self.dynamic_css_provider, Some(&scss))
+                    if let Err(e) = self.reload_style(file, watch) {
+                        return cli::Response::Error(e.to_string());
+                    }
+                }
+                cli::style::Command::Default => {
+                    self.watch_style_files(&[]);
+                    self.style_path = None;
+                    // The default style is always loaded underneath
+                    self.dynamic_css_provider.load_from_string("");
+                    event::publish(event::Event::StyleReloaded { file: None });
                 }
-                cli::style::Command::Default => Self::load_style(&self.dynamic_css_provider, None),
             },
+            Command::Subscribe { .. } => {
+                return cli::Response::Error(format!(
+                    "Subscriptions must be made through the unix socket"
+                ));
+            }
+            Command::Extra(command) => return WM::handle_command(command),
         }
         cli::Response::Success(format!("Ok"))
     }
 
-    fn load_style(provider: &gtk::CssProvider, scss: Option<&str>) {
-        let css = match grass::from_string(
-            scss.unwrap_or(include_str!("./style.scss")),
-            &grass::Options::default(),
-        ) {
-            Ok(css) => css,
+    fn load_default_style(provider: &gtk::CssProvider) {
+        match style::compile_default() {
+            Ok(css) => Self::apply_css(provider, &css),
+            Err(e) => log::warning!("Failed to compile sass style: {e}"),
+        }
+    }
+
+    /// Compiles a style file, also watching it and everything it imports if `watch` is set
+    fn reload_style(&mut self, file: PathBuf, watch: bool) -> Result<(), error::StyleError> {
+        if !watch {
+            self.watch_style_files(&[]);
+        }
+
+        let compiled = match style::compile_file(&file) {
+            Ok(compiled) => compiled,
             Err(e) => {
-                log::warning!("Failed to compile sass style: {e}");
-                return;
+                // Keep watching, so fixing the error reloads the style
+                if watch && self.style_monitors.is_empty() {
+                    self.watch_style_files(&[file.clone()]);
+                }
+                self.style_path = Some(file);
+                return Err(e);
             }
         };
 
-        provider.load_from_string(&css);
+        Self::apply_css(&self.dynamic_css_provider, &compiled.css);
+
+        if watch {
+            self.watch_style_files(&compiled.files);
+        }
+
+        self.style_path = Some(file.clone());
+        event::publish(event::Event::StyleReloaded { file: Some(file) });
+        Ok(())
+    }
+
+    /// Replaces the watched style files
+    fn watch_style_files(&mut self, files: &[PathBuf]) {
+        for monitor in self.style_monitors.drain(..) {
+            monitor.cancel();
+        }
+
+        for file in files {
+            match gio::File::for_path(file)
+                .monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => {
+                    monitor.connect_changed(|_, _, _, event| match event {
+                        gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created => {
+                            style::notify_changed();
+                        }
+                        _ => {}
+                    });
+                    self.style_monitors.push(monitor);
+                }
+                Err(e) => log::warning!("Failed to watch style file {:?}: {}", file, e),
+            }
+        }
+    }
+
+    /// Recompiles styles when a watched file or the style variables change
+    fn listen_style_changes(state: &Rc<RefCell<Self>>) {
+        let receiver = style::changed();
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak]
+            state,
+            async move {
+                while receiver.recv_async().await.is_ok() {
+                    // Saving may touch several files at once
+                    while receiver.try_recv().is_ok() {}
+
+                    Self::load_default_style(&state.borrow().default_css_provider);
+
+                    let Some(file) = state.borrow().style_path.clone() else {
+                        continue;
+                    };
+
+                    let watch = !state.borrow().style_monitors.is_empty();
+                    match state.borrow_mut().reload_style(file.clone(), watch) {
+                        Ok(()) => log::message!("Style reloaded from {:?}", file),
+                        Err(e) => {
+                            log::warning!("{}", e);
+                            event::publish(event::Event::StyleFailed {
+                                file,
+                                error: e.to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+        ));
+    }
+
+    fn apply_css(provider: &gtk::CssProvider, css: &str) {
+        provider.load_from_string(css);
 
         if let Some(display) = gtk::gdk::Display::default() {
             #[allow(deprecated)] // it's not really deprecated
@@ -336,38 +952,313 @@ where
         }
     }
 
-    fn create_all_windows(&mut self, app: &gtk::Application) {
-        // TODO: Check if window already exists (match monitor and window id)
-        for (id, cfg) in self.config.windows.clone().iter() {
-            if cfg.lazy {
-                continue;
-            }
+    /// Reloads the config whenever the config file or one of its includes changes
+    fn watch_config(state: &Rc<RefCell<Self>>, app: &gtk::Application) {
+        let receiver = state.borrow().config_changed.1.clone();
 
-            match &cfg.monitor {
-                monitor::Monitor::Dynamic => {
-                    self.create_window(&id, None, app);
-                }
-                monitor::Monitor::Single(monitor_match) => {
-                    for monitor in monitor_match.find_matches() {
-                        self.create_window(&id, Some(monitor), app);
+        glib::spawn_future_local(glib::clone!(
+            #[weak]
+            app,
+            #[weak]
+            state,
+            async move {
+                while receiver.recv_async().await.is_ok() {
+                    // Saving may touch several files at once
+                    while receiver.try_recv().is_ok() {}
+
+                    match state.borrow_mut().reload_config(None, &app) {
+                        cli::Response::Success(ans) => log::message!("{}", ans),
+                        cli::Response::Error(e) => log::warning!("{}", e),
                     }
                 }
-                monitor::Monitor::Multi(monitor_matches) => {
-                    for monitor_match in monitor_matches {
-                        for monitor in monitor_match.find_matches() {
-                            self.create_window(&id, Some(monitor), app);
+            }
+        ));
+
+        state.borrow_mut().watch_config_files();
+    }
+
+    /// Replaces the watched config files with the last loaded ones
+    fn watch_config_files(&mut self) {
+        for monitor in self.config_monitors.drain(..) {
+            monitor.cancel();
+        }
+
+        // Not loaded yet, creating it triggers a reload
+        let files = match (&self.config_files[..], &self.config_path) {
+            ([], Some(config_path)) => vec![config_path.clone()],
+            (files, _) => files.to_vec(),
+        };
+
+        for file in files {
+            match gio::File::for_path(&file)
+                .monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => {
+                    let sender = self.config_changed.0.clone();
+                    monitor.connect_changed(move |_, _, _, event| match event {
+                        gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created => {
+                            drop(sender.send(()));
                         }
-                    }
-                }
-                monitor::Monitor::All => {
-                    for monitor in monitor::monitors() {
-                        self.create_window(&id, Some(monitor), app);
-                    }
+                        _ => {}
+                    });
+                    log::message!("Watching config file {:?}", file);
+                    self.config_monitors.push(monitor);
                 }
+                Err(e) => log::warning!("Failed to watch config file {:?}: {}", file, e),
+            }
+        }
+    }
+
+    /// Re-parses the config file and rebuilds only the windows whose template changed
+    fn reload_config(&mut self, file: Option<PathBuf>, app: &gtk::Application) -> cli::Response {
+        // Loaded once, the daemon keeps watching and saving next to its own config file
+        let one_off = file.is_some();
+        let Some(config_path) = file.or(self.config_path.clone()) else {
+            return cli::Response::Error(format!("No config file to reload"));
+        };
+
+        let mut files = vec![];
+        let config = match Self::load_config(&config_path, &mut files) {
+            Ok(config) => config,
+            Err(e) => {
+                return cli::Response::Error(format!(
+                    "Failed to reload config from {config_path:?}: {e}"
+                ));
+            }
+        };
+
+        let old_config = std::mem::replace(&mut self.config, config);
+        if !one_off {
+            self.config_files = files;
+
+            // Includes may have been added or removed
+            if !self.config_monitors.is_empty() {
+                self.watch_config_files();
+            }
+        }
+
+        let mut changed = vec![];
+
+        for (id, old_cfg) in &old_config.windows {
+            let template_changed = match self.config.windows.get(id) {
+                Some(new_cfg) => Self::window_config_changed(old_cfg, new_cfg),
+                None => true,
+            };
+
+            if template_changed {
+                self.close_windows_from_template(id);
+                changed.push(id.clone());
+            }
+        }
+
+        for id in self.config.windows.keys() {
+            if !old_config.windows.contains_key(id) {
+                changed.push(id.clone());
+            }
+        }
+
+        changed.sort();
+
+        if !self
+            .layout
+            .as_ref()
+            .is_some_and(|name| self.config.layouts.contains_key(name))
+        {
+            self.layout = self.config.layout.clone();
+        }
+
+        self.sync_layout(app);
+
+        event::publish(event::Event::ConfigReloaded {
+            file: config_path.clone(),
+            templates: changed.clone(),
+        });
+
+        cli::Response::Success(format!(
+            "Config reloaded from {config_path:?}, updated templates: {changed:?}"
+        ))
+    }
+
+    fn window_config_changed(
+        old: &config::window::Config<WM::ConfigWrapper>,
+        new: &config::window::Config<WM::ConfigWrapper>,
+    ) -> bool {
+        match (serde_json::to_value(old), serde_json::to_value(new)) {
+            (Ok(old), Ok(new)) => old != new,
+            _ => true,
+        }
+    }
+
+    /// Switches layout, validating it before creating or closing any window
+    fn switch_layout(
+        &mut self,
+        name: config::layout::Name,
+        app: &gtk::Application,
+    ) -> cli::Response {
+        let Some(layout) = self.config.layouts.get(&name) else {
+            return cli::Response::Error(format!("Layout {name} not found"));
+        };
+
+        if let Some(id) = layout
+            .windows
+            .iter()
+            .find(|id| !self.config.windows.contains_key(*id))
+        {
+            return cli::Response::Error(format!(
+                "Layout {name} references unknown window template {id}"
+            ));
+        }
+
+        self.layout = Some(name.clone());
+        self.sync_layout(app);
+
+        event::publish(event::Event::LayoutSwitched { name: name.clone() });
+
+        if let Err(e) = Self::save_layout_state(&name) {
+            return cli::Response::Error(format!(
+                "Switched to layout {name}, but failed to persist it: {e}"
+            ));
+        }
+
+        cli::Response::Success(format!("Switched to layout {name}"))
+    }
+
+    fn is_window_active(&self, id: &config::window::Id) -> bool {
+        let Some(layout) = self
+            .layout
+            .as_ref()
+            .and_then(|name| self.config.layouts.get(name))
+        else {
+            return true;
+        };
+        layout.windows.contains(id)
+    }
+
+    /// Creates windows that became active and closes the ones that aren't active anymore
+    fn sync_layout(&mut self, app: &gtk::Application) {
+        self.create_all_windows(app);
+
+        let inactive: Vec<config::window::Id> = self
+            .windows
+            .values()
+            .map(|instance| instance.template_id.clone())
+            .filter(|id| !self.is_window_active(id))
+            .collect();
+
+        for id in inactive {
+            self.close_windows_from_template(&id);
+        }
+    }
+
+    fn layout_state_path() -> PathBuf {
+        glib::user_data_dir().join(WM::LAYOUT_STATE_PATH)
+    }
+
+    fn load_layout_state() -> Option<config::layout::Name> {
+        let name = std::fs::read_to_string(Self::layout_state_path()).ok()?;
+        let name = name.trim();
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    fn save_layout_state(name: &config::layout::Name) -> std::io::Result<()> {
+        let path = Self::layout_state_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, name)
+    }
+
+    fn watch_monitors(state: &Rc<RefCell<Self>>, app: &gtk::Application) {
+        let Some(display) = gdk::Display::default() else {
+            log::warning!("Failed to get default display, monitor hotplug disabled");
+            return;
+        };
+
+        display.monitors().connect_items_changed(glib::clone!(
+            #[weak]
+            app,
+            #[weak]
+            state,
+            move |_, _, _, _| state.borrow_mut().sync_monitors(&app)
+        ));
+    }
+
+    /// Closes windows bound to vanished monitors and creates windows for new ones
+    fn sync_monitors(&mut self, app: &gtk::Application) {
+        let connectors: Vec<monitor::Id> = monitor::monitors().iter().map(monitor::id).collect();
+
+        self.windows.retain(|uuid, instance| {
+            let Some(connector) = &instance.monitor else {
+                return true;
+            };
+            if connectors.contains(connector) {
+                return true;
+            }
+            log::message!(
+                "Monitor {} disconnected, closing window {} ({})",
+                connector,
+                instance.template_id,
+                uuid
+            );
+            Self::destroy_window(uuid, instance);
+            false
+        });
+
+        event::publish(event::Event::MonitorsChanged { connectors });
+
+        self.create_all_windows(app);
+    }
+
+    /// Creates every active non-lazy window that doesn't exist yet for its matching monitors
+    fn create_all_windows(&mut self, app: &gtk::Application) {
+        for (id, cfg) in self.config.windows.clone().iter() {
+            if cfg.lazy || !self.is_window_active(id) {
+                continue;
+            }
+
+            self.create_windows_from_template(id, &cfg.monitor, app);
+        }
+    }
+
+    fn create_windows_from_template(
+        &mut self,
+        id: &config::window::Id,
+        monitor: &monitor::Monitor,
+        app: &gtk::Application,
+    ) {
+        for monitor in monitor.resolve() {
+            if self.has_window(id, monitor.as_ref()) {
+                continue;
             }
+            self.create_window(&id, monitor, app);
         }
     }
 
+    fn has_window(&self, id: &config::window::Id, monitor: Option<&gdk::Monitor>) -> bool {
+        let connector = monitor.map(monitor::id);
+        self.windows
+            .values()
+            .any(|instance| instance.template_id == *id && instance.monitor == connector)
+    }
+
+    fn close_windows_from_template(&mut self, id: &config::window::Id) {
+        self.windows.retain(|uuid, instance| {
+            if instance.template_id != *id {
+                return true;
+            }
+            Self::destroy_window(uuid, instance);
+            false
+        });
+    }
+
+    fn destroy_window(uuid: &str, instance: &WindowInstance<WM::WindowWrapper>) {
+        WM::unwrap_window(&instance.wrapper).destroy();
+        event::publish(event::Event::WindowClosed {
+            uuid: uuid.to_string(),
+            template: instance.template_id.clone(),
+        });
+    }
+
     fn create_window(
         &mut self,
         id: &config::window::Id,
@@ -386,50 +1277,180 @@ where
 
         if let Some(layer_shell) = &window_cfg.layer_shell {
             window.init_layer_shell();
-            window.set_namespace(Some(&layer_shell.namespace));
-            window.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::OnDemand);
-            window.set_layer((&layer_shell.layer).into());
             window.add_css_class(&layer_shell.namespace);
-            for anchor in &layer_shell.anchors {
-                window.set_anchor(anchor.into(), true);
-            }
-            match layer_shell.exclusive_zone {
-                config::layer_shell::ExclusiveZone::Auto => {
-                    window.auto_exclusive_zone_enable();
-                }
-                config::layer_shell::ExclusiveZone::Ignore => {
-                    window.set_exclusive_zone(-1);
-                }
-                config::layer_shell::ExclusiveZone::Normal => {
-                    window.set_exclusive_zone(0);
-                }
-            }
+            layer_shell.apply(window);
         }
 
         window.set_monitor(monitor.as_ref());
 
         app.add_window(window);
-        window.present();
+        if !window_cfg.hidden {
+            window.present();
+        }
+
+        let uuid = uuid::Uuid::new_v4().to_string();
+        let connector = monitor.as_ref().map(monitor::id);
+
+        event::publish(event::Event::WindowCreated {
+            uuid: uuid.clone(),
+            template: id.clone(),
+            monitor: connector.clone(),
+        });
 
         self.windows.insert(
-            uuid::Uuid::new_v4().to_string(),
-            (id.clone(), wrapped_window),
+            uuid,
+            WindowInstance {
+                template_id: id.clone(),
+                monitor: connector,
+                wrapper: wrapped_window,
+            },
         );
     }
 
-    fn config_to_string(&self, json: bool) -> String {
-        if json {
-            serde_json::to_string_pretty(&self.config).unwrap()
-        } else {
-            use ron::extensions::Extensions;
-            ron::ser::to_string_pretty(
-                &self.config,
-                ron::ser::PrettyConfig::default()
-                    .extensions(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_VARIANT_NEWTYPES),
-            )
-            .unwrap()
+    /// Config describing what is running right now
+    fn live_config(&self) -> Config<WM::ConfigWrapper, WM::WidgetTemplate> {
+        let mut windows = HashMap::new();
+
+        for (id, template) in &self.config.windows {
+            let mut template = template.clone();
+            let instances: Vec<&WindowInstance<WM::WindowWrapper>> = self
+                .windows
+                .values()
+                .filter(|instance| instance.template_id == *id)
+                .collect();
+
+            if let Some(instance) = instances.first() {
+                let window = WM::unwrap_window(&instance.wrapper);
+                template.lazy = false;
+                template.hidden = instances
+                    .iter()
+                    .all(|instance| !WM::unwrap_window(&instance.wrapper).is_visible());
+                template.layer_shell = template
+                    .layer_shell
+                    .map(|layer_shell| layer_shell.read(window));
+                template.monitor = Self::live_monitor(&template.monitor, &instances);
+            } else if self.is_window_active(id) {
+                // Closed at runtime
+                template.lazy = true;
+            }
+
+            windows.insert(id.clone(), template);
+        }
+
+        Config {
+            include: vec![],
+            templates: self.config.templates.clone(),
+            windows,
+            layouts: self.config.layouts.clone(),
+            layout: self.layout.clone(),
+        }
+    }
+
+    /// Keeps the configured monitor if it still describes the running instances
+    fn live_monitor(
+        configured: &monitor::Monitor,
+        instances: &[&WindowInstance<WM::WindowWrapper>],
+    ) -> monitor::Monitor {
+        let mut running: Vec<Option<monitor::Id>> = instances
+            .iter()
+            .map(|instance| instance.monitor.clone())
+            .collect();
+        let mut expected: Vec<Option<monitor::Id>> = configured
+            .resolve()
+            .iter()
+            .map(|monitor| monitor.as_ref().map(monitor::id))
+            .collect();
+        running.sort();
+        expected.sort();
+
+        if running == expected {
+            return configured.clone();
+        }
+
+        let connectors: Option<Vec<monitor::Id>> = running.into_iter().collect();
+        match connectors {
+            None => monitor::Monitor::Dynamic,
+            Some(connectors) if connectors.len() == 1 => monitor::Monitor::Single(
+                monitor::MonitorMatch::with_connector(connectors[0].clone()),
+            ),
+            Some(connectors) => monitor::Monitor::Multi(
+                connectors
+                    .into_iter()
+                    .map(monitor::MonitorMatch::with_connector)
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Writes the live config, flattened into a single file
+    ///
+    /// Saves next to the loaded config unless a path is given, and never overwrites a config
+    /// file that uses includes or templates as they would be lost
+    fn save_config(&self, path: Option<PathBuf>) -> cli::Response {
+        let path = match (path, &self.config_path) {
+            (Some(path), _) => path,
+            (None, Some(config_path)) => Self::saved_config_path(config_path),
+            (None, None) => return cli::Response::Error(format!("No config file to save to")),
+        };
+
+        let has_layers = self.config_files.len() > 1 || !self.config.templates.is_empty();
+        let overwrites_config = match path.canonicalize() {
+            Ok(canonical) => self
+                .config_files
+                .iter()
+                .any(|file| file.canonicalize().ok().as_ref() == Some(&canonical)),
+            Err(_) => false,
+        };
+
+        if has_layers && overwrites_config {
+            return cli::Response::Error(format!(
+                "{path:?} is part of a config using includes or templates, saving would flatten it, pick another path"
+            ));
+        }
+
+        let text = config::format::Format::from_path(&path)
+            .and_then(|format| format.serialize(&self.live_config()));
+
+        match text.map(|text| std::fs::write(&path, text)) {
+            Ok(Ok(())) => cli::Response::Success(format!("Config saved to {path:?}")),
+            Ok(Err(e)) => cli::Response::Error(format!("Failed to write {path:?}: {e}")),
+            Err(e) => cli::Response::Error(format!("Failed to save config: {e}")),
+        }
+    }
+
+    /// `config.ron` is saved to `config.saved.ron`
+    fn saved_config_path(config_path: &PathBuf) -> PathBuf {
+        let stem = config_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        match config_path.extension() {
+            Some(ext) => {
+                config_path.with_file_name(format!("{stem}.saved.{}", ext.to_string_lossy()))
+            }
+            None => config_path.with_file_name(format!("{stem}.saved")),
         }
     }
+
+    /// Serializes the current config, `json` overrides `format`
+    fn config_to_string(
+        &self,
+        json: bool,
+        format: config::format::Format,
+    ) -> Result<String, error::ConfigError> {
+        let format = if json {
+            config::format::Format::Json
+        } else {
+            format
+        };
+        format.serialize(&self.config)
+    }
+
+    /// JSON Schema of the config file, so editors can validate and autocomplete it
+    fn config_schema() -> String {
+        let schema = schemars::schema_for!(Config<WM::ConfigWrapper, WM::WidgetTemplate>);
+        serde_json::to_string_pretty(&schema).unwrap()
+    }
 }
 
 pub trait AzaleaAppExt
@@ -438,16 +1459,44 @@ where
 {
     type ConfigWrapper: serde::Serialize
         + serde::de::DeserializeOwned
+        + schemars::JsonSchema
+        + std::fmt::Debug
+        + Clone
+        + 'static;
+    /// Reusable widgets, see config::Config::templates
+    type WidgetTemplate: serde::Serialize
+        + serde::de::DeserializeOwned
+        + schemars::JsonSchema
         + std::fmt::Debug
         + Clone
         + 'static;
     type WindowWrapper;
+    /// Extra subcommands, flattened into the main command line commands
+    type Command: clap::Subcommand
+        + serde::Serialize
+        + serde::de::DeserializeOwned
+        + std::fmt::Debug
+        + 'static;
 
     const CONFIG_PATH: &str = "azalea/config.ron";
     const STYLE_PATH: &str = "azalea/style.scss";
     const SOCKET_NAME: &str = "azalea.sock";
+    const LAYOUT_STATE_PATH: &str = "azalea/layout";
     const APP_ID: &str = "br.usp.ime.Azalea";
 
     fn create_window(config: &Self::ConfigWrapper) -> Self::WindowWrapper;
     fn unwrap_window(window: &Self::WindowWrapper) -> &gtk::Window;
+
+    /// Checks a window config for mistakes that parse fine, returns one message per problem
+    fn validate(_config: &Self::ConfigWrapper) -> Vec<String> {
+        vec![]
+    }
+
+    /// Runs once when the daemon starts
+    fn activate(_app: &gtk::Application) {}
+
+    /// Handles extra subcommands, runs on the daemon
+    fn handle_command(command: Self::Command) -> cli::Response {
+        cli::Response::Error(format!("Command not supported: {command:?}"))
+    }
 }
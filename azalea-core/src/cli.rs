@@ -1,13 +1,16 @@
 use clap::{Parser, arg, command};
 
-use crate::log;
+use crate::{event, log};
 
 /// Command line arguments
 #[derive(clap::Parser, serde::Serialize)]
 #[command(version, about, long_about = None)]
-pub struct Arguments {
+pub struct Arguments<Extra>
+where
+    Extra: clap::Subcommand,
+{
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Command<Extra>,
 
     /// Wait for daemon to start
     #[clap(short, long)]
@@ -18,7 +21,10 @@ pub struct Arguments {
     pub gtk_options: Vec<String>,
 }
 
-impl Arguments {
+impl<Extra> Arguments<Extra>
+where
+    Extra: clap::Subcommand,
+{
     pub fn parse(after_help: impl clap::builder::IntoResettable<clap::builder::StyledStr>) -> Self {
         let mut matches = <Self as clap::CommandFactory>::command()
             .after_help(after_help)
@@ -35,7 +41,10 @@ impl Arguments {
 
 /// Main command line commands
 #[derive(Parser, serde::Serialize, serde::Deserialize, Debug)]
-pub enum Command {
+pub enum Command<Extra>
+where
+    Extra: clap::Subcommand,
+{
     #[command(subcommand)]
     Daemon(daemon::Command),
 
@@ -52,7 +61,19 @@ pub enum Command {
 
     #[command(subcommand)]
     Style(style::Command),
-    // TODO: Extra subcommand given by the user?
+
+    #[command(subcommand)]
+    Layout(layout::Command),
+
+    /// Stream events as newline-delimited json, the connection only carries events afterwards
+    Subscribe {
+        /// Topics to subscribe to, all of them if none is given
+        topics: Vec<event::Topic>,
+    },
+
+    /// Subcommands given by the application (see AzaleaAppExt::Command)
+    #[command(flatten)]
+    Extra(Extra),
 }
 
 pub mod daemon {
@@ -61,18 +82,32 @@ pub mod daemon {
         Start {
             #[clap(long)]
             config: Option<String>,
+
+            /// Reload config whenever the config file changes
+            #[clap(long)]
+            watch_config: bool,
+
+            /// Refuse to start with the default config if the config file is invalid
+            #[clap(long)]
+            strict: bool,
         },
         Stop,
     }
 }
 
 pub mod window {
-    use crate::config;
+    use crate::{config, monitor};
 
     #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
     pub enum Command {
         Create(CreateArgs),
         Toggle(ToggleArgs),
+        /// Destroy windows
+        Close(Selector),
+        Show(Selector),
+        Hide(Selector),
+        /// Move windows to another monitor
+        SetMonitor(SetMonitorArgs),
         Uuid,
     }
 
@@ -84,17 +119,110 @@ pub mod window {
     #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
     pub struct CreateArgs {
         pub id: config::window::Id,
+
+        /// Create one window for each matching monitor, or a dynamic one if empty
+        #[command(flatten)]
+        pub monitor: monitor::MonitorMatch,
+    }
+
+    /// Selects windows by uuid, or by template id and monitor
+    #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
+    pub struct Selector {
+        pub uuid: Option<String>,
+
+        #[clap(long, conflicts_with = "uuid")]
+        pub template: Option<config::window::Id>,
+
+        #[command(flatten)]
+        pub monitor: monitor::MonitorMatch,
+    }
+
+    #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
+    pub struct SetMonitorArgs {
+        #[command(flatten)]
+        pub selector: Selector,
+
+        /// Connector of the target monitor
+        #[clap(long)]
+        pub to: monitor::Id,
     }
 }
 
 pub mod layer_shell {
     use gtk4_layer_shell::LayerShell;
 
-    use crate::config::layer_shell::{Anchor, Layer};
+    use crate::config::layer_shell::{Anchor, ExclusiveZone, KeyboardMode, Layer};
 
     #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
     pub enum Command {
         Toggle(Arguments),
+        /// Change layer shell properties of matching windows
+        Set(SetArguments),
+    }
+
+    #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
+    pub struct SetArguments {
+        #[command(flatten)]
+        pub filter: Arguments,
+
+        #[clap(long)]
+        pub set_layer: Option<Layer>,
+
+        /// Replaces all anchors, pass it without values to remove them
+        #[clap(long, num_args = 0.., value_delimiter = ',')]
+        pub set_anchors: Option<Vec<Anchor>>,
+
+        /// auto, ignore, normal or a number of pixels
+        #[clap(long, allow_hyphen_values = true)]
+        pub set_exclusive_zone: Option<ExclusiveZone>,
+
+        #[clap(long)]
+        pub set_keyboard_mode: Option<KeyboardMode>,
+
+        #[clap(long, allow_hyphen_values = true)]
+        pub set_margin_top: Option<i32>,
+
+        #[clap(long, allow_hyphen_values = true)]
+        pub set_margin_bottom: Option<i32>,
+
+        #[clap(long, allow_hyphen_values = true)]
+        pub set_margin_left: Option<i32>,
+
+        #[clap(long, allow_hyphen_values = true)]
+        pub set_margin_right: Option<i32>,
+    }
+
+    impl SetArguments {
+        pub fn apply(&self, window: &gtk::Window) {
+            if let Some(layer) = &self.set_layer {
+                window.set_layer(layer.into());
+            }
+
+            if let Some(anchors) = &self.set_anchors {
+                for anchor in &Anchor::ALL {
+                    window.set_anchor(anchor.into(), anchors.contains(anchor));
+                }
+            }
+
+            if let Some(exclusive_zone) = &self.set_exclusive_zone {
+                exclusive_zone.apply(window);
+            }
+
+            if let Some(keyboard_mode) = &self.set_keyboard_mode {
+                window.set_keyboard_mode(keyboard_mode.into());
+            }
+
+            for (anchor, margin) in [
+                (Anchor::Top, self.set_margin_top),
+                (Anchor::Bottom, self.set_margin_bottom),
+                (Anchor::Left, self.set_margin_left),
+                (Anchor::Right, self.set_margin_right),
+            ] {
+                if let Some(margin) = margin {
+                    window.set_margin((&anchor).into(), margin);
+                }
+            }
+        }
     }
 
     #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
@@ -136,11 +264,50 @@ pub mod layer_shell {
 }
 
 pub mod config {
+    use crate::config::format::Format;
+
     #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
     pub enum Command {
         View {
-            #[clap(long)]
+            /// Same as --format json
+            #[clap(long, conflicts_with = "format")]
             json: bool,
+
+            #[clap(long, value_enum, default_value_t)]
+            format: Format,
+        },
+        /// Reload config file, rebuilding only the windows that changed
+        Reload {
+            /// Path to config file, loaded once without replacing the watched config file
+            #[clap(long)]
+            file: Option<std::path::PathBuf>,
+        },
+        /// Write the running windows, with their monitors, visibility and layer shell
+        /// properties, to a config file
+        Save {
+            /// Path to config file, next to the loaded one with a `.saved` suffix if not given.
+            /// Files of a config using includes or templates can't be overwritten
+            path: Option<std::path::PathBuf>,
+        },
+        /// Print the JSON Schema of the config file
+        Schema,
+        /// Check a config file for errors, doesn't need a running daemon. Monitors are only
+        /// checked by the daemon when it starts
+        Validate {
+            /// Path to config file
+            path: Option<std::path::PathBuf>,
+        },
+        /// Translate a config file to another format, doesn't need a running daemon
+        Convert {
+            /// Path to config file
+            input: std::path::PathBuf,
+
+            /// Output file, printed if not given
+            output: Option<std::path::PathBuf>,
+
+            /// Output format, guessed from the output file extension if not given
+            #[clap(long, value_enum)]
+            format: Option<Format>,
         },
     }
 }
@@ -153,12 +320,28 @@ pub mod style {
             /// Path to css file
             #[clap(long)]
             file: Option<std::path::PathBuf>,
+
+            /// Recompile whenever the file or anything it imports changes
+            #[clap(long)]
+            watch: bool,
         },
         /// Loads default css style
         Default,
     }
 }
 
+pub mod layout {
+    use crate::config;
+
+    #[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug)]
+    pub enum Command {
+        /// Switch to another layout, creating and closing windows accordingly
+        Switch { name: config::layout::Name },
+        /// List available layouts
+        List,
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub enum Response {
     Success(String),
@@ -1,13 +1,16 @@
 //! Configuration structs
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 pub mod layer_shell {
     use clap::Parser;
+    use gtk4_layer_shell::LayerShell;
 
     pub type Namespace = String;
 
-    #[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[derive(
+        clap::ValueEnum, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone,
+    )]
     pub enum Layer {
         Background,
         Bottom,
@@ -15,6 +18,17 @@ pub mod layer_shell {
         Overlay,
     }
 
+    impl From<gtk4_layer_shell::Layer> for Layer {
+        fn from(value: gtk4_layer_shell::Layer) -> Self {
+            match value {
+                gtk4_layer_shell::Layer::Background => Layer::Background,
+                gtk4_layer_shell::Layer::Bottom => Layer::Bottom,
+                gtk4_layer_shell::Layer::Overlay => Layer::Overlay,
+                _ => Layer::Top,
+            }
+        }
+    }
+
     impl Into<gtk4_layer_shell::Layer> for &Layer {
         fn into(self) -> gtk4_layer_shell::Layer {
             match self {
@@ -26,7 +40,15 @@ pub mod layer_shell {
         }
     }
 
-    #[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[derive(
+        clap::ValueEnum,
+        serde::Serialize,
+        serde::Deserialize,
+        schemars::JsonSchema,
+        Debug,
+        Clone,
+        PartialEq,
+    )]
     pub enum Anchor {
         Top,
         Bottom,
@@ -45,7 +67,12 @@ pub mod layer_shell {
         }
     }
 
-    #[derive(clap::ValueEnum, Default, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    impl Anchor {
+        pub const ALL: [Anchor; 4] = [Anchor::Top, Anchor::Bottom, Anchor::Left, Anchor::Right];
+    }
+
+    /// Parsed from `auto`, `ignore`, `normal` or a number of pixels
+    #[derive(Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone)]
     pub enum ExclusiveZone {
         /// Let gtk-layer-shell calculate it
         Auto,
@@ -56,9 +83,79 @@ pub mod layer_shell {
         /// 0
         #[default]
         Normal,
+
+        /// Reserve a fixed amount of pixels
+        Size(i32),
+    }
+
+    impl std::str::FromStr for ExclusiveZone {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_lowercase().as_str() {
+                "auto" => Ok(ExclusiveZone::Auto),
+                "ignore" => Ok(ExclusiveZone::Ignore),
+                "normal" => Ok(ExclusiveZone::Normal),
+                size => size.parse().map(ExclusiveZone::Size).map_err(|_| {
+                    format!("Expected auto, ignore, normal or a number of pixels, found {s}")
+                }),
+            }
+        }
+    }
+
+    impl ExclusiveZone {
+        pub fn apply(&self, window: &gtk::Window) {
+            match self {
+                ExclusiveZone::Auto => window.auto_exclusive_zone_enable(),
+                ExclusiveZone::Ignore => window.set_exclusive_zone(-1),
+                ExclusiveZone::Normal => window.set_exclusive_zone(0),
+                ExclusiveZone::Size(size) => window.set_exclusive_zone(*size),
+            }
+        }
+    }
+
+    #[derive(
+        clap::ValueEnum,
+        Default,
+        serde::Serialize,
+        serde::Deserialize,
+        schemars::JsonSchema,
+        Debug,
+        Clone,
+    )]
+    pub enum KeyboardMode {
+        /// Never receive keyboard focus
+        None,
+
+        /// Grab keyboard focus while visible
+        Exclusive,
+
+        /// Receive keyboard focus when clicked
+        #[default]
+        OnDemand,
+    }
+
+    impl From<gtk4_layer_shell::KeyboardMode> for KeyboardMode {
+        fn from(value: gtk4_layer_shell::KeyboardMode) -> Self {
+            match value {
+                gtk4_layer_shell::KeyboardMode::None => KeyboardMode::None,
+                gtk4_layer_shell::KeyboardMode::Exclusive => KeyboardMode::Exclusive,
+                _ => KeyboardMode::OnDemand,
+            }
+        }
+    }
+
+    impl Into<gtk4_layer_shell::KeyboardMode> for &KeyboardMode {
+        fn into(self) -> gtk4_layer_shell::KeyboardMode {
+            match self {
+                KeyboardMode::None => gtk4_layer_shell::KeyboardMode::None,
+                KeyboardMode::Exclusive => gtk4_layer_shell::KeyboardMode::Exclusive,
+                KeyboardMode::OnDemand => gtk4_layer_shell::KeyboardMode::OnDemand,
+            }
+        }
     }
 
-    #[derive(Parser, serde::Serialize, serde::Deserialize, Debug, Clone)]
+    #[derive(Parser, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone)]
     pub struct Config {
         pub namespace: Namespace,
 
@@ -68,8 +165,147 @@ pub mod layer_shell {
         #[clap(long)]
         pub anchors: Vec<Anchor>,
 
-        #[clap(long)]
+        #[clap(long, allow_hyphen_values = true)]
         pub exclusive_zone: ExclusiveZone,
+
+        #[clap(long, value_enum, default_value_t)]
+        #[serde(default)]
+        pub keyboard_mode: KeyboardMode,
+
+        #[command(flatten)]
+        #[serde(default)]
+        pub margins: Margins,
+
+        /// Default width, the window may still grow to fit its content
+        #[clap(long)]
+        #[serde(default)]
+        pub width: Option<i32>,
+
+        /// Default height, the window may still grow to fit its content
+        #[clap(long)]
+        #[serde(default)]
+        pub height: Option<i32>,
+    }
+
+    impl Config {
+        pub fn apply(&self, window: &gtk::Window) {
+            window.set_namespace(Some(&self.namespace));
+            window.set_keyboard_mode((&self.keyboard_mode).into());
+            window.set_layer((&self.layer).into());
+            for anchor in &self.anchors {
+                window.set_anchor(anchor.into(), true);
+            }
+            self.exclusive_zone.apply(window);
+            self.margins.apply(window);
+
+            if self.width.is_some() || self.height.is_some() {
+                window.set_default_size(self.width.unwrap_or(-1), self.height.unwrap_or(-1));
+            }
+        }
+
+        /// Current state of a window, which may have been changed through the cli
+        pub fn read(&self, window: &gtk::Window) -> Self {
+            let exclusive_zone = if window.auto_exclusive_zone_is_enabled() {
+                ExclusiveZone::Auto
+            } else {
+                match window.exclusive_zone() {
+                    -1 => ExclusiveZone::Ignore,
+                    0 => ExclusiveZone::Normal,
+                    size => ExclusiveZone::Size(size),
+                }
+            };
+
+            Self {
+                namespace: window
+                    .namespace()
+                    .map(|namespace| namespace.to_string())
+                    .unwrap_or(self.namespace.clone()),
+                layer: window.layer().into(),
+                anchors: Anchor::ALL
+                    .iter()
+                    .filter(|anchor| window.is_anchor((*anchor).into()))
+                    .cloned()
+                    .collect(),
+                exclusive_zone,
+                keyboard_mode: window.keyboard_mode().into(),
+                margins: Margins {
+                    top: window.margin(gtk4_layer_shell::Edge::Top),
+                    bottom: window.margin(gtk4_layer_shell::Edge::Bottom),
+                    left: window.margin(gtk4_layer_shell::Edge::Left),
+                    right: window.margin(gtk4_layer_shell::Edge::Right),
+                },
+                width: self.width,
+                height: self.height,
+            }
+        }
+    }
+
+    /// Distance in pixels from each anchored edge
+    #[derive(
+        Parser, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone,
+    )]
+    pub struct Margins {
+        #[clap(long = "margin-top", default_value_t, allow_hyphen_values = true)]
+        #[serde(default)]
+        pub top: i32,
+
+        #[clap(long = "margin-bottom", default_value_t, allow_hyphen_values = true)]
+        #[serde(default)]
+        pub bottom: i32,
+
+        #[clap(long = "margin-left", default_value_t, allow_hyphen_values = true)]
+        #[serde(default)]
+        pub left: i32,
+
+        #[clap(long = "margin-right", default_value_t, allow_hyphen_values = true)]
+        #[serde(default)]
+        pub right: i32,
+    }
+
+    impl Margins {
+        pub fn apply(&self, window: &gtk::Window) {
+            window.set_margin(gtk4_layer_shell::Edge::Top, self.top);
+            window.set_margin(gtk4_layer_shell::Edge::Bottom, self.bottom);
+            window.set_margin(gtk4_layer_shell::Edge::Left, self.left);
+            window.set_margin(gtk4_layer_shell::Edge::Right, self.right);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::ExclusiveZone;
+
+        #[test]
+        fn exclusive_zone_from_str() {
+            assert!(matches!(
+                "auto".parse::<ExclusiveZone>(),
+                Ok(ExclusiveZone::Auto)
+            ));
+            assert!(matches!(
+                "Ignore".parse::<ExclusiveZone>(),
+                Ok(ExclusiveZone::Ignore)
+            ));
+            assert!(matches!(
+                "NORMAL".parse::<ExclusiveZone>(),
+                Ok(ExclusiveZone::Normal)
+            ));
+            assert!(matches!(
+                "32".parse::<ExclusiveZone>(),
+                Ok(ExclusiveZone::Size(32))
+            ));
+            assert!(matches!(
+                "-1".parse::<ExclusiveZone>(),
+                Ok(ExclusiveZone::Size(-1))
+            ));
+        }
+
+        #[test]
+        fn exclusive_zone_from_invalid_str() {
+            for invalid in ["", "full", "1.5", "99999999999"] {
+                let error = invalid.parse::<ExclusiveZone>().unwrap_err();
+                assert!(error.ends_with(&format!("found {invalid}")), "{error}");
+            }
+        }
     }
 }
 
@@ -81,7 +317,7 @@ pub mod window {
     pub type Id = String;
 
     /// Template configuration for a window
-    #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
+    #[derive(Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug)]
     pub struct Config<ConfigWrapper>
     where
         ConfigWrapper: std::fmt::Debug + Clone,
@@ -90,16 +326,656 @@ pub mod window {
         pub layer_shell: Option<layer_shell::Config>,
         #[serde(default)]
         pub lazy: bool,
+        /// Create the window without showing it
+        #[serde(default)]
+        pub hidden: bool,
         #[serde(default)]
         pub monitor: Monitor,
     }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct Config<ConfigWrapper>
+pub mod layout {
+    use super::window;
+
+    pub type Name = String;
+
+    /// Set of window templates that are active at the same time
+    #[derive(Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Default)]
+    pub struct Config {
+        pub windows: Vec<window::Id>,
+    }
+}
+
+pub mod format {
+    use std::path::Path;
+
+    use crate::error::ConfigError;
+
+    /// Config file formats
+    #[derive(
+        clap::ValueEnum,
+        serde::Serialize,
+        serde::Deserialize,
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Default,
+    )]
+    pub enum Format {
+        #[default]
+        Ron,
+        Json,
+        Toml,
+        Yaml,
+    }
+
+    impl Format {
+        /// Picks the format by file extension, anything unknown is parsed as RON
+        pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+            let ext = path.extension().ok_or(ConfigError::MissingExtension)?;
+            Ok(match ext.to_str() {
+                Some("json") => Format::Json,
+                Some("toml") => Format::Toml,
+                Some("yaml" | "yml") => Format::Yaml,
+                _ => Format::Ron,
+            })
+        }
+
+        pub fn parse<T>(&self, text: &str) -> Result<T, ConfigError>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            match self {
+                Format::Ron => ron::from_str(text).map_err(ron_error),
+                Format::Json => serde_json::from_str(text).map_err(json_error),
+                Format::Toml => toml::from_str(text).map_err(|e| toml_error(e, text)),
+                Format::Yaml => serde_yaml_ng::from_str(text).map_err(yaml_error),
+            }
+        }
+
+        pub fn serialize<T>(&self, value: &T) -> Result<String, ConfigError>
+        where
+            T: serde::Serialize,
+        {
+            match self {
+                Format::Ron => {
+                    use ron::extensions::Extensions;
+                    ron::ser::to_string_pretty(
+                        value,
+                        ron::ser::PrettyConfig::default().extensions(
+                            Extensions::IMPLICIT_SOME | Extensions::UNWRAP_VARIANT_NEWTYPES,
+                        ),
+                    )
+                    .map_err(|e| ConfigError::SerializationError(e.to_string()))
+                }
+                Format::Json => serde_json::to_string_pretty(value)
+                    .map_err(|e| ConfigError::SerializationError(e.to_string())),
+                Format::Toml => toml::to_string_pretty(value)
+                    .map_err(|e| ConfigError::SerializationError(e.to_string())),
+                Format::Yaml => serde_yaml_ng::to_string(value)
+                    .map_err(|e| ConfigError::SerializationError(e.to_string())),
+            }
+        }
+    }
+
+    pub(super) fn ron_error(e: ron::error::SpannedError) -> ConfigError {
+        ConfigError::SyntaxError {
+            line: e.position.line,
+            column: e.position.col,
+            message: e.code.to_string(),
+        }
+    }
+
+    pub(super) fn json_error(e: serde_json::Error) -> ConfigError {
+        syntax_error(e.to_string(), e.line(), e.column())
+    }
+
+    pub(super) fn toml_error(e: toml::de::Error, text: &str) -> ConfigError {
+        let start = e.span().map(|span| span.start).unwrap_or(0);
+        let (line, column) = position(text, start);
+        ConfigError::SyntaxError {
+            line,
+            column,
+            message: e.message().to_string(),
+        }
+    }
+
+    pub(super) fn yaml_error(e: serde_yaml_ng::Error) -> ConfigError {
+        match e.location() {
+            Some(location) => syntax_error(e.to_string(), location.line(), location.column()),
+            None => ConfigError::ParsingError(e.to_string()),
+        }
+    }
+
+    /// Removes the location some parsers append to their messages
+    fn syntax_error(message: String, line: usize, column: usize) -> ConfigError {
+        let location = format!(" at line {line} column {column}");
+        ConfigError::SyntaxError {
+            line,
+            column,
+            message: message.replace(&location, ""),
+        }
+    }
+
+    /// Line and column, starting at 1, of a byte offset
+    fn position(text: &str, offset: usize) -> (usize, usize) {
+        let before = &text[..offset.min(text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        (line, column)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Sample {
+            name: String,
+            size: Option<u32>,
+            ratio: f64,
+            items: Vec<Item>,
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Inner {
+            enabled: bool,
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        enum Item {
+            Unit,
+            Newtype(Inner),
+            Value(i32),
+        }
+
+        fn sample() -> Sample {
+            Sample {
+                name: format!("bar \"top\""),
+                size: Some(3),
+                ratio: 0.5,
+                items: vec![
+                    Item::Unit,
+                    Item::Newtype(Inner { enabled: true }),
+                    Item::Value(-2),
+                ],
+            }
+        }
+
+        #[test]
+        fn parse_round_trips() {
+            for format in [Format::Ron, Format::Json, Format::Toml, Format::Yaml] {
+                let text = format.serialize(&sample()).unwrap();
+                let parsed: Sample = format.parse(&text).unwrap();
+                assert_eq!(parsed, sample(), "{format:?}:\n{text}");
+            }
+        }
+
+        #[test]
+        fn parse_ron() {
+            let text = "Sample(name: r#\"raw \"str\"\"#, size: None, ratio: inf, items: [Unit, Newtype((enabled: false)), Value(1)])";
+            let parsed: Sample = Format::Ron.parse(text).unwrap();
+
+            assert_eq!(parsed.name, "raw \"str\"");
+            assert_eq!(parsed.size, None);
+            assert_eq!(parsed.ratio, f64::INFINITY);
+            assert_eq!(
+                parsed.items,
+                [
+                    Item::Unit,
+                    Item::Newtype(Inner { enabled: false }),
+                    Item::Value(1)
+                ]
+            );
+        }
+
+        #[test]
+        fn syntax_error_position() {
+            let cases = [
+                (Format::Ron, "(\n  a: 1,\n  b: @,\n)", 3, Some(6)),
+                (Format::Json, "{\n  \"a\": 1,\n  \"b\": @\n}", 3, None),
+                (Format::Toml, "a = 1\nb = 2\nc = @\n", 3, None),
+            ];
+
+            for (format, text, expected_line, expected_column) in cases {
+                match format.parse::<serde_json::Value>(text) {
+                    Err(ConfigError::SyntaxError {
+                        line,
+                        column,
+                        message,
+                    }) => {
+                        assert_eq!(line, expected_line, "{format:?}");
+                        if let Some(expected_column) = expected_column {
+                            assert_eq!(column, expected_column, "{format:?}");
+                        }
+                        assert!(!message.contains(" at line "), "{format:?}: {message}");
+                    }
+                    other => panic!("{format:?}: expected a syntax error, got {other:?}"),
+                }
+            }
+        }
+
+        #[test]
+        fn position_counts_from_one() {
+            assert_eq!(position("ab\ncd", 0), (1, 1));
+            assert_eq!(position("ab\ncd", 4), (2, 2));
+            assert_eq!(position("ab", 100), (1, 3));
+        }
+    }
+}
+
+pub mod template {
+    use std::{cell::RefCell, collections::HashMap};
+
+    pub type Name = String;
+
+    /// Guards against templates that refer to each other
+    pub const MAX_DEPTH: usize = 16;
+
+    /// Template as written in its file, deserialized where it's referenced so each format keeps
+    /// its own semantics
+    #[derive(Clone, Debug)]
+    pub enum Source {
+        Ron {
+            text: String,
+            extensions: ron::extensions::Extensions,
+        },
+        Json(serde_json::Value),
+        Toml(toml::Value),
+        Yaml(serde_yaml_ng::Value),
+    }
+
+    impl Source {
+        fn deserialize<T>(&self) -> Result<T, String>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            match self {
+                Source::Ron { text, extensions } => ron::Options::default()
+                    .with_default_extension(*extensions)
+                    .from_str(text)
+                    .map_err(|e| e.to_string()),
+                Source::Json(value) => {
+                    serde_json::from_value(value.clone()).map_err(|e| e.to_string())
+                }
+                Source::Toml(value) => value.clone().try_into().map_err(|e| e.to_string()),
+                Source::Yaml(value) => {
+                    serde_yaml_ng::from_value(value.clone()).map_err(|e| e.to_string())
+                }
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Scope {
+        templates: HashMap<Name, Source>,
+        depth: usize,
+    }
+
+    thread_local! {
+        static SCOPE: RefCell<Scope> = RefCell::default();
+    }
+
+    /// Runs `f` with the templates that references found by `resolve` refer to
+    pub fn scope<R>(templates: HashMap<Name, Source>, f: impl FnOnce() -> R) -> R {
+        let previous = SCOPE.replace(Scope {
+            templates,
+            depth: 0,
+        });
+        let result = f();
+        SCOPE.set(previous);
+        result
+    }
+
+    /// Deserializes the named template, called by widgets when they find a reference while
+    /// deserializing in `scope`
+    pub fn resolve<T>(name: &str) -> Result<T, String>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let source = SCOPE.with_borrow_mut(|scope| {
+            if scope.depth >= MAX_DEPTH {
+                return Err(format!("Widget template {name} refers to itself"));
+            }
+            let source = scope.templates.get(name).cloned();
+            let source = source.ok_or_else(|| format!("Unknown widget template {name}"))?;
+            scope.depth += 1;
+            Ok(source)
+        })?;
+
+        let result = source.deserialize();
+        SCOPE.with_borrow_mut(|scope| scope.depth -= 1);
+        result
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::config::format::Format;
+
+        /// Stands in for a widget enum, with the extra variant referring to templates
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        #[serde(try_from = "WidgetOrTemplate")]
+        enum Widget {
+            Clock(u32),
+            Group(Vec<Widget>),
+        }
+
+        #[derive(serde::Deserialize)]
+        enum WidgetOrTemplate {
+            Clock(u32),
+            Group(Vec<Widget>),
+            Template(Name),
+        }
+
+        impl TryFrom<WidgetOrTemplate> for Widget {
+            type Error = String;
+
+            fn try_from(widget: WidgetOrTemplate) -> Result<Self, Self::Error> {
+                match widget {
+                    WidgetOrTemplate::Clock(config) => Ok(Widget::Clock(config)),
+                    WidgetOrTemplate::Group(config) => Ok(Widget::Group(config)),
+                    WidgetOrTemplate::Template(name) => resolve(&name),
+                }
+            }
+        }
+
+        fn templates(entries: &[(&str, &str)]) -> HashMap<Name, Source> {
+            entries
+                .iter()
+                .map(|(name, text)| {
+                    let source = Source::Ron {
+                        text: text.to_string(),
+                        extensions: ron::extensions::Extensions::empty(),
+                    };
+                    (name.to_string(), source)
+                })
+                .collect()
+        }
+
+        #[test]
+        fn replaces_references() {
+            let templates = templates(&[
+                ("clock", "Clock(1)"),
+                ("group", "Group([Template(\"clock\"), Clock(2)])"),
+            ]);
+
+            let widgets: Vec<Widget> = scope(templates, || {
+                Format::Ron.parse("[Template(\"group\"), Clock(3)]")
+            })
+            .unwrap();
+
+            assert_eq!(
+                widgets,
+                [
+                    Widget::Group(vec![Widget::Clock(1), Widget::Clock(2)]),
+                    Widget::Clock(3)
+                ]
+            );
+        }
+
+        #[test]
+        fn resolves_other_formats() {
+            let templates = HashMap::from([
+                (
+                    format!("json"),
+                    Source::Json(serde_json::json!({"Clock": 1})),
+                ),
+                (
+                    format!("toml"),
+                    Source::Toml(toml::from_str("Clock = 2").unwrap()),
+                ),
+                (
+                    format!("yaml"),
+                    Source::Yaml(serde_yaml_ng::from_str("!Clock 3").unwrap()),
+                ),
+            ]);
+
+            let widgets: Vec<Widget> = scope(templates, || {
+                Format::Json
+                    .parse(r#"[{"Template": "json"}, {"Template": "toml"}, {"Template": "yaml"}]"#)
+            })
+            .unwrap();
+
+            assert_eq!(
+                widgets,
+                [Widget::Clock(1), Widget::Clock(2), Widget::Clock(3)]
+            );
+        }
+
+        #[test]
+        fn unknown_template() {
+            let result: Result<Widget, _> = scope(HashMap::new(), || {
+                Format::Ron.parse("Template(\"missing\")")
+            });
+
+            match result {
+                Err(e) => assert!(e.to_string().contains("Unknown widget template missing")),
+                Ok(widget) => panic!("Expected an error, got {widget:?}"),
+            }
+        }
+
+        #[test]
+        fn recursive_template() {
+            let templates =
+                templates(&[("a", "Template(\"b\")"), ("b", "Group([Template(\"a\")])")]);
+
+            let result: Result<Widget, _> =
+                scope(templates, || Format::Ron.parse("Template(\"a\")"));
+
+            match result {
+                Err(e) => assert!(e.to_string().contains("refers to itself"), "{e}"),
+                Ok(widget) => panic!("Expected an error, got {widget:?}"),
+            }
+            assert_eq!(SCOPE.with_borrow(|scope| scope.depth), 0);
+        }
+    }
+}
+
+/// A config file read before its windows are deserialized, so the templates of every included
+/// file are known by then
+pub mod file {
+    use std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+    };
+
+    use serde::Deserialize;
+
+    use super::{
+        format::{self, Format},
+        template,
+    };
+    use crate::error::ConfigError;
+
+    /// Fields needed before deserializing the rest, other fields are skipped
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Config", bound = "Template: serde::Deserialize<'de>")]
+    struct Layer<Template> {
+        #[serde(default)]
+        include: Vec<PathBuf>,
+        #[serde(default)]
+        templates: HashMap<template::Name, Template>,
+    }
+
+    pub struct File {
+        pub path: PathBuf,
+        pub include: Vec<PathBuf>,
+        pub templates: HashMap<template::Name, template::Source>,
+        format: Format,
+        text: String,
+    }
+
+    impl File {
+        pub fn read(path: &Path) -> Result<Self, ConfigError> {
+            let format = Format::from_path(path)?;
+            let text = std::fs::read_to_string(path)?;
+
+            let (include, templates) = match format {
+                Format::Ron => {
+                    let mut deserializer =
+                        ron::Deserializer::from_str(&text).map_err(format::ron_error)?;
+                    let layer = Layer::<Box<ron::value::RawValue>>::deserialize(&mut deserializer)
+                        .map_err(|e| format::ron_error(deserializer.span_error(e)))?;
+                    let extensions = deserializer.extensions();
+                    let templates = layer.templates.into_iter().map(|(name, raw)| {
+                        let text = raw.trim().get_ron().to_string();
+                        (name, template::Source::Ron { text, extensions })
+                    });
+                    (layer.include, templates.collect())
+                }
+                Format::Json => {
+                    let layer: Layer<serde_json::Value> =
+                        serde_json::from_str(&text).map_err(format::json_error)?;
+                    let templates = layer.templates.into_iter();
+                    let templates =
+                        templates.map(|(name, value)| (name, template::Source::Json(value)));
+                    (layer.include, templates.collect())
+                }
+                Format::Toml => {
+                    let layer: Layer<toml::Value> =
+                        toml::from_str(&text).map_err(|e| format::toml_error(e, &text))?;
+                    let templates = layer.templates.into_iter();
+                    let templates =
+                        templates.map(|(name, value)| (name, template::Source::Toml(value)));
+                    (layer.include, templates.collect())
+                }
+                Format::Yaml => {
+                    let layer: Layer<serde_yaml_ng::Value> =
+                        serde_yaml_ng::from_str(&text).map_err(format::yaml_error)?;
+                    let templates = layer.templates.into_iter();
+                    let templates =
+                        templates.map(|(name, value)| (name, template::Source::Yaml(value)));
+                    (layer.include, templates.collect())
+                }
+            };
+
+            Ok(Self {
+                path: path.to_path_buf(),
+                include,
+                templates,
+                format,
+                text,
+            })
+        }
+
+        /// Included files, relative to this one
+        pub fn includes(&self) -> impl Iterator<Item = PathBuf> {
+            self.include.iter().map(|include| match self.path.parent() {
+                Some(dir) => dir.join(include),
+                None => include.clone(),
+            })
+        }
+
+        /// Deserializes the whole file, template references are resolved if called in
+        /// `template::scope`
+        pub fn parse<T>(&self) -> Result<T, ConfigError>
+        where
+            T: serde::de::DeserializeOwned,
+        {
+            self.format.parse(&self.text)
+        }
+    }
+
+    /// Templates of every file, later files take precedence like their windows do
+    pub fn templates(files: &[File]) -> HashMap<template::Name, template::Source> {
+        files
+            .iter()
+            .flat_map(|file| file.templates.clone())
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn write(name: &str, text: &str) -> PathBuf {
+            let dir = std::env::temp_dir().join(format!("azalea-config-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join(name);
+            std::fs::write(&path, text).unwrap();
+            path
+        }
+
+        #[test]
+        fn reads_includes_and_templates() {
+            let path = write(
+                "main.ron",
+                "#![enable(implicit_some)]\nConfig(include: [\"other.json\"], templates: {\"sep\": Separator(text: \"|\")}, windows: {})",
+            );
+
+            let file = File::read(&path).unwrap();
+            assert_eq!(
+                file.includes().collect::<Vec<_>>(),
+                [path.with_file_name("other.json")]
+            );
+
+            match &file.templates["sep"] {
+                template::Source::Ron { text, extensions } => {
+                    assert_eq!(text, "Separator(text: \"|\")");
+                    assert!(extensions.contains(ron::extensions::Extensions::IMPLICIT_SOME));
+                }
+                other => panic!("Expected a RON template, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn reads_other_formats() {
+            let cases = [
+                (
+                    "a.json",
+                    r#"{"templates": {"sep": {"Separator": {}}}, "windows": {}}"#,
+                ),
+                ("a.toml", "[templates.sep.Separator]\n[windows]\n"),
+                ("a.yaml", "templates:\n  sep: !Separator {}\nwindows: {}\n"),
+            ];
+
+            for (name, text) in cases {
+                let file = File::read(&write(name, text)).unwrap();
+                assert!(file.include.is_empty(), "{name}");
+                assert!(file.templates.contains_key("sep"), "{name}");
+            }
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct Config<ConfigWrapper, WidgetTemplate = ()>
 where
     ConfigWrapper: std::fmt::Debug + Clone,
 {
+    /// Other config files merged into this one, relative to this file.
+    /// Their windows, layouts and templates take precedence.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+
+    /// Named widgets, referenced with `Template(name)` where a widget is expected
+    #[serde(default)]
+    pub templates: HashMap<template::Name, WidgetTemplate>,
+
     pub windows: HashMap<window::Id, window::Config<ConfigWrapper>>,
-    // TODO: Add different layouts (which windows are active)
+
+    /// Named layouts, each one listing which windows are active
+    #[serde(default)]
+    pub layouts: HashMap<layout::Name, layout::Config>,
+
+    /// Layout used when none was switched to, all windows are active if unset
+    #[serde(default)]
+    pub layout: Option<layout::Name>,
+}
+
+impl<ConfigWrapper, WidgetTemplate> Config<ConfigWrapper, WidgetTemplate>
+where
+    ConfigWrapper: std::fmt::Debug + Clone,
+{
+    /// Merges an included config, its entries replace the ones with the same name
+    pub fn merge(&mut self, other: Self) {
+        self.templates.extend(other.templates);
+        self.windows.extend(other.windows);
+        self.layouts.extend(other.layouts);
+        if other.layout.is_some() {
+            self.layout = other.layout;
+        }
+    }
 }
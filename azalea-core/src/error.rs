@@ -1,9 +1,40 @@
+#[derive(thiserror::Error, Debug)]
+pub enum StyleError {
+    #[error("Failed to find style file {0:?}")]
+    NotFound(std::path::PathBuf),
+    #[error("{}:{line}:{column}: {message}", .file.display())]
+    Compile {
+        file: std::path::PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("Failed to compile style: {0}")]
+    Other(String),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ConfigError {
     #[error("Failed to read config file")]
     Io(#[from] std::io::Error),
     #[error("Missing extension")]
     MissingExtension,
-    #[error("Parsing error")]
+    #[error("Parsing error: {0}")]
     ParsingError(String),
+    #[error("Parsing error at line {line}, column {column}: {message}")]
+    SyntaxError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+    #[error("In included file {path:?}: {source}")]
+    Include {
+        path: std::path::PathBuf,
+        source: Box<ConfigError>,
+    },
+    /// Config parsed, but refers to things that don't exist
+    #[error("Invalid config:\n  {}", .0.join("\n  "))]
+    Invalid(Vec<String>),
 }
@@ -0,0 +1,109 @@
+//! Events streamed to clients subscribed through the unix socket
+
+use std::{
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+};
+
+use crate::{config, log, monitor};
+
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Topic {
+    Window,
+    Style,
+    Config,
+    Layout,
+    Monitor,
+    Service,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub enum Event {
+    WindowCreated {
+        uuid: String,
+        template: config::window::Id,
+        monitor: Option<monitor::Id>,
+    },
+    WindowClosed {
+        uuid: String,
+        template: config::window::Id,
+    },
+    StyleReloaded {
+        file: Option<PathBuf>,
+    },
+    /// A watched style failed to compile, the previous style is kept
+    StyleFailed {
+        file: PathBuf,
+        error: String,
+    },
+    ConfigReloaded {
+        file: PathBuf,
+        templates: Vec<config::window::Id>,
+    },
+    LayoutSwitched {
+        name: config::layout::Name,
+    },
+    MonitorsChanged {
+        connectors: Vec<monitor::Id>,
+    },
+    /// A service emitted an output, only its name is sent as outputs aren't serializable
+    ServiceOutput {
+        service: String,
+    },
+}
+
+impl Event {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Event::WindowCreated { .. } | Event::WindowClosed { .. } => Topic::Window,
+            Event::StyleReloaded { .. } | Event::StyleFailed { .. } => Topic::Style,
+            Event::ConfigReloaded { .. } => Topic::Config,
+            Event::LayoutSwitched { .. } => Topic::Layout,
+            Event::MonitorsChanged { .. } => Topic::Monitor,
+            Event::ServiceOutput { .. } => Topic::Service,
+        }
+    }
+}
+
+struct Subscriber {
+    topics: Vec<Topic>,
+    sender: flume::Sender<Event>,
+}
+
+impl Subscriber {
+    fn wants(&self, topic: Topic) -> bool {
+        self.topics.is_empty() || self.topics.contains(&topic)
+    }
+}
+
+static SUBSCRIBERS: LazyLock<Mutex<Vec<Subscriber>>> = LazyLock::new(Default::default);
+
+/// Subscribes to the given topics, or to every topic if none is given
+pub fn subscribe(topics: Vec<Topic>) -> flume::Receiver<Event> {
+    let (sender, receiver) = flume::unbounded();
+
+    match SUBSCRIBERS.lock() {
+        Ok(mut subscribers) => subscribers.push(Subscriber { topics, sender }),
+        Err(e) => log::warning!("Failed to lock event subscribers: {}", e),
+    }
+
+    receiver
+}
+
+pub fn has_subscribers(topic: Topic) -> bool {
+    SUBSCRIBERS
+        .lock()
+        .map(|subscribers| subscribers.iter().any(|s| s.wants(topic)))
+        .unwrap_or(false)
+}
+
+/// Sends event to every interested subscriber, dropping the disconnected ones
+pub fn publish(event: Event) {
+    let topic = event.topic();
+
+    let Ok(mut subscribers) = SUBSCRIBERS.lock() else {
+        return;
+    };
+
+    subscribers.retain(|s| !s.wants(topic) || s.sender.send(event.clone()).is_ok());
+}
@@ -5,12 +5,16 @@
 //! - command line arguments
 //! - configuration structs
 //! - client-server through unix sockets
+//! - event streaming to socket subscribers
+//! - scss compilation
 
 pub mod app;
 pub mod cli;
 pub mod config;
 pub mod dbus;
 pub mod error;
+pub mod event;
 use azalea_log as log;
 pub mod monitor;
 pub mod socket;
+pub mod style;
@@ -13,13 +13,27 @@ use gtk::{
 pub type Id = String;
 
 /// Used to find a specific monitor
-#[derive(Default, clap::Parser, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(
+    Default, clap::Parser, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone,
+)]
 pub struct MonitorMatch {
+    #[clap(long)]
     connector: Option<Id>,
+    #[clap(long)]
     manufacturer: Option<String>,
+    #[clap(long)]
     model: Option<String>,
 }
 
+impl MonitorMatch {
+    pub fn with_connector(connector: Id) -> Self {
+        Self {
+            connector: Some(connector),
+            ..Default::default()
+        }
+    }
+}
+
 impl From<gdk::Monitor> for MonitorMatch {
     fn from(value: gdk::Monitor) -> Self {
         Self {
@@ -30,7 +44,7 @@ impl From<gdk::Monitor> for MonitorMatch {
     }
 }
 
-#[derive(Default, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema, Debug, Clone)]
 pub enum Monitor {
     /// Monitor is determined on-the-fly (good for popups)
     Dynamic,
@@ -46,6 +60,24 @@ pub enum Monitor {
     All,
 }
 
+impl Monitor {
+    /// Monitors that should have an instance of the window, None means dynamic
+    pub fn resolve(&self) -> Vec<Option<gdk::Monitor>> {
+        match self {
+            Monitor::Dynamic => vec![None],
+            Monitor::Single(monitor_match) => {
+                monitor_match.find_matches().into_iter().map(Some).collect()
+            }
+            Monitor::Multi(monitor_matches) => monitor_matches
+                .iter()
+                .flat_map(|monitor_match| monitor_match.find_matches())
+                .map(Some)
+                .collect(),
+            Monitor::All => monitors().into_iter().map(Some).collect(),
+        }
+    }
+}
+
 pub fn all() -> Vec<gdk::Monitor> {
     let monitors = gdk::Display::default().unwrap().monitors();
 
@@ -62,53 +94,53 @@ pub fn all() -> Vec<gdk::Monitor> {
 }
 
 impl MonitorMatch {
-    pub fn find_matches(&self) -> Vec<gdk::Monitor> {
-        let monitors = gdk::Display::default().unwrap().monitors();
-
-        let mut matched_monitors = vec![];
-
-        for i in 0..monitors.n_items() {
-            let Some(monitor): Option<gdk::Monitor> = monitors.item(i).and_downcast() else {
-                continue;
-            };
-
-            if let Some(connector) = &self.connector {
-                if monitor
-                    .connector()
-                    .map(|v| v.to_string())
-                    .unwrap_or(format!(""))
-                    != *connector
-                {
-                    continue;
-                }
-            }
+    /// Whether no criteria are set
+    pub fn is_empty(&self) -> bool {
+        self.connector.is_none() && self.manufacturer.is_none() && self.model.is_none()
+    }
 
-            if let Some(manufacturer) = &self.manufacturer {
-                if monitor
-                    .manufacturer()
-                    .map(|v| v.to_string())
-                    .unwrap_or(format!(""))
-                    != *manufacturer
-                {
-                    continue;
-                }
+    pub fn matches(&self, monitor: &gdk::Monitor) -> bool {
+        if let Some(connector) = &self.connector {
+            if monitor
+                .connector()
+                .map(|v| v.to_string())
+                .unwrap_or(format!(""))
+                != *connector
+            {
+                return false;
             }
+        }
 
-            if let Some(model) = &self.model {
-                if monitor
-                    .model()
-                    .map(|v| v.to_string())
-                    .unwrap_or(format!(""))
-                    != *model
-                {
-                    continue;
-                }
+        if let Some(manufacturer) = &self.manufacturer {
+            if monitor
+                .manufacturer()
+                .map(|v| v.to_string())
+                .unwrap_or(format!(""))
+                != *manufacturer
+            {
+                return false;
             }
+        }
 
-            matched_monitors.push(monitor);
+        if let Some(model) = &self.model {
+            if monitor
+                .model()
+                .map(|v| v.to_string())
+                .unwrap_or(format!(""))
+                != *model
+            {
+                return false;
+            }
         }
 
-        return matched_monitors;
+        return true;
+    }
+
+    pub fn find_matches(&self) -> Vec<gdk::Monitor> {
+        monitors()
+            .into_iter()
+            .filter(|monitor| self.matches(monitor))
+            .collect()
     }
 }
 
@@ -126,6 +158,14 @@ pub fn monitors() -> Vec<gdk::Monitor> {
     output
 }
 
+/// Monitor connector, or an empty string if the compositor didn't provide one
+pub fn id(monitor: &gdk::Monitor) -> Id {
+    monitor
+        .connector()
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+}
+
 pub fn monitors_to_string() -> String {
     let output: Vec<HashMap<&str, String>> = monitors()
         .iter()
@@ -1,16 +1,139 @@
+//! Framed protocol used by the control socket
+//!
+//! Every message is sent as a frame:
+//!
+//! | version (u16) | request id (u32) | length (u32) | json payload |
+//!
+//! Header fields are big endian. A connection starts with a handshake, where
+//! client and daemon exchange an empty frame to check if their versions match.
+//! After that, several requests can be sent through the same connection, each
+//! reply carries the id of the request it answers.
+//!
+//! A subscribe request turns the connection into a stream of newline-delimited
+//! json events, so plain socket clients can read them without decoding frames.
+
+/// Bumped whenever the frame layout or the serialized commands change
+pub const PROTOCOL_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 10;
+const MAX_PAYLOAD_SIZE: u32 = 16 * 1024 * 1024;
+const HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+pub type RequestId = u32;
+
 #[derive(Debug)]
 pub enum Error {
     Read,
     Write,
     UnixSocket(String),
+    /// Connection was closed by the other end
+    Closed,
+    /// Handshake wasn't answered in time, the other end may not speak this protocol
+    Timeout,
+    PayloadTooLarge(u32),
+    Incompatible {
+        local: u16,
+        remote: u16,
+    },
+}
+
+/// Message received through the socket
+#[derive(Debug)]
+pub struct Frame<T> {
+    pub id: RequestId,
+    pub payload: T,
+}
+
+struct Header {
+    version: u16,
+    id: RequestId,
+    length: u32,
+}
+
+impl Header {
+    fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        bytes[0..2].copy_from_slice(&self.version.to_be_bytes());
+        bytes[2..6].copy_from_slice(&self.id.to_be_bytes());
+        bytes[6..10].copy_from_slice(&self.length.to_be_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &[u8; HEADER_SIZE]) -> Self {
+        Self {
+            version: u16::from_be_bytes([bytes[0], bytes[1]]),
+            id: u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]),
+            length: u32::from_be_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]),
+        }
+    }
+
+    fn check(self) -> Result<Self, Error> {
+        if self.version != PROTOCOL_VERSION {
+            return Err(Error::Incompatible {
+                local: PROTOCOL_VERSION,
+                remote: self.version,
+            });
+        }
+        if self.length > MAX_PAYLOAD_SIZE {
+            return Err(Error::PayloadTooLarge(self.length));
+        }
+        Ok(self)
+    }
+}
+
+fn encode<E>(id: RequestId, payload: E) -> Result<Vec<u8>, Error>
+where
+    E: serde::Serialize,
+{
+    let payload = serde_json::to_vec(&payload).map_err(|_| Error::Write)?;
+    let header = Header {
+        version: PROTOCOL_VERSION,
+        id,
+        length: u32::try_from(payload.len()).map_err(|_| Error::Write)?,
+    };
+
+    let mut frame = Vec::with_capacity(HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&header.encode());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+fn decode<T>(header: &Header, payload: &[u8]) -> Result<Frame<T>, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    match serde_json::from_slice(payload) {
+        Ok(payload) => Ok(Frame {
+            id: header.id,
+            payload,
+        }),
+        Err(_) => Err(Error::Read),
+    }
+}
+
+fn encode_line<E>(payload: E) -> Result<Vec<u8>, Error>
+where
+    E: serde::Serialize,
+{
+    let mut line = serde_json::to_vec(&payload).map_err(|_| Error::Write)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+fn read_error(error: std::io::Error) -> Error {
+    match error.kind() {
+        std::io::ErrorKind::UnexpectedEof => Error::Closed,
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => Error::Timeout,
+        _ => Error::Read,
+    }
 }
 
 pub mod sync {
     use crate::log;
 
-    use super::Error;
+    use super::{Error, Frame, HEADER_SIZE, Header, RequestId};
     use std::{
-        io::{Read, Write},
+        io::{BufRead, Read, Write},
         os::unix::net::{UnixListener, UnixStream},
     };
 
@@ -56,11 +179,12 @@ pub mod sync {
 
     pub struct UnixStreamWrapper {
         stream: UnixStream,
+        last_id: RequestId,
     }
 
     impl UnixStreamWrapper {
         pub fn new(stream: UnixStream) -> Self {
-            Self { stream }
+            Self { stream, last_id: 0 }
         }
 
         pub fn connect<P>(path: P) -> Result<Self, Error>
@@ -73,31 +197,82 @@ pub mod sync {
             }
         }
 
-        pub fn read<T>(&mut self) -> Result<T, Error>
+        /// Client side of the handshake, fails if the daemon speaks another version
+        pub fn handshake(&mut self) -> Result<(), Error> {
+            self.write(0, ())?;
+
+            drop(self.stream.set_read_timeout(Some(super::HANDSHAKE_TIMEOUT)));
+            let header = self.read_header();
+            drop(self.stream.set_read_timeout(None));
+
+            let header = header?.check()?;
+            self.read_payload(&header)?;
+            Ok(())
+        }
+
+        /// Daemon side of the handshake, always answers so the client knows our version
+        pub fn accept_handshake(&mut self) -> Result<(), Error> {
+            drop(self.stream.set_read_timeout(Some(super::HANDSHAKE_TIMEOUT)));
+            let header = self.read_header();
+            drop(self.stream.set_read_timeout(None));
+
+            let header = header?;
+            self.write(0, ())?;
+            let header = header.check()?;
+            self.read_payload(&header)?;
+            Ok(())
+        }
+
+        fn read_header(&mut self) -> Result<Header, Error> {
+            let mut bytes = [0; HEADER_SIZE];
+            self.stream
+                .read_exact(&mut bytes)
+                .map_err(super::read_error)?;
+            Ok(Header::decode(&bytes))
+        }
+
+        fn read_payload(&mut self, header: &Header) -> Result<Vec<u8>, Error> {
+            let mut payload = vec![0; header.length as usize];
+            self.stream
+                .read_exact(&mut payload)
+                .map_err(super::read_error)?;
+            Ok(payload)
+        }
+
+        pub fn read<T>(&mut self) -> Result<Frame<T>, Error>
         where
             T: serde::de::DeserializeOwned,
         {
-            let mut response = vec![];
-            drop(self.stream.read_to_end(&mut response));
-            match serde_json::from_slice(&response) {
-                Ok(response) => Ok(response),
-                Err(_) => Err(Error::Read),
-            }
+            let header = self.read_header()?.check()?;
+            let payload = self.read_payload(&header)?;
+            super::decode(&header, &payload)
         }
 
-        pub fn write<E>(&mut self, payload: E) -> Result<(), Error>
+        pub fn write<E>(&mut self, id: RequestId, payload: E) -> Result<(), Error>
         where
             E: serde::Serialize,
         {
-            let ans = match self
-                .stream
-                .write_all(&serde_json::to_vec(&payload).unwrap())
-            {
+            match self.stream.write_all(&super::encode(id, payload)?) {
                 Ok(_) => Ok(()),
                 Err(_) => Err(Error::Write),
-            };
-            drop(self.stream.shutdown(std::net::Shutdown::Write));
-            ans
+            }
+        }
+
+        /// Sends a request with a new id, replies will carry the same id
+        pub fn send<E>(&mut self, payload: E) -> Result<RequestId, Error>
+        where
+            E: serde::Serialize,
+        {
+            self.last_id = self.last_id.wrapping_add(1).max(1);
+            self.write(self.last_id, payload)?;
+            Ok(self.last_id)
+        }
+
+        /// Reads newline-delimited messages until the connection closes
+        pub fn lines(self) -> impl Iterator<Item = String> {
+            std::io::BufReader::new(self.stream)
+                .lines()
+                .map_while(Result::ok)
         }
     }
 }
@@ -105,11 +280,21 @@ pub mod sync {
 pub mod r#async {
     use crate::log;
 
-    use super::Error;
+    use super::{Error, Frame, HEADER_SIZE, Header, RequestId};
     use futures_lite::io::{AsyncReadExt, AsyncWriteExt};
 
     use async_net::unix::{UnixListener, UnixStream};
 
+    /// Fails with `Error::Timeout` if the handshake isn't done in time, so a silent peer
+    /// doesn't hold the connection forever
+    async fn timeout<T>(handshake: impl Future<Output = Result<T, Error>>) -> Result<T, Error> {
+        futures_lite::future::or(handshake, async {
+            async_io::Timer::after(super::HANDSHAKE_TIMEOUT).await;
+            Err(Error::Timeout)
+        })
+        .await
+    }
+
     pub struct UnixListenerWrapper {
         listener: UnixListener,
     }
@@ -147,39 +332,204 @@ pub mod r#async {
 
     pub struct UnixStreamWrapper {
         stream: UnixStream,
+        last_id: RequestId,
     }
 
     impl UnixStreamWrapper {
         pub fn new(stream: UnixStream) -> Self {
-            Self { stream }
+            Self { stream, last_id: 0 }
+        }
+
+        pub async fn connect<P>(path: P) -> Result<Self, Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            match UnixStream::connect(path).await {
+                Ok(stream) => Ok(UnixStreamWrapper::new(stream)),
+                Err(e) => Err(Error::UnixSocket(e.to_string())),
+            }
+        }
+
+        /// Client side of the handshake, fails if the daemon speaks another version
+        pub async fn handshake(&mut self) -> Result<(), Error> {
+            timeout(async {
+                self.write(0, ()).await?;
+                let header = self.read_header().await?.check()?;
+                self.read_payload(&header).await?;
+                Ok(())
+            })
+            .await
+        }
+
+        /// Daemon side of the handshake, always answers so the client knows our version
+        pub async fn accept_handshake(&mut self) -> Result<(), Error> {
+            timeout(async {
+                let header = self.read_header().await?;
+                self.write(0, ()).await?;
+                let header = header.check()?;
+                self.read_payload(&header).await?;
+                Ok(())
+            })
+            .await
+        }
+
+        async fn read_header(&mut self) -> Result<Header, Error> {
+            let mut bytes = [0; HEADER_SIZE];
+            self.stream
+                .read_exact(&mut bytes)
+                .await
+                .map_err(super::read_error)?;
+            Ok(Header::decode(&bytes))
         }
 
-        pub async fn read<T>(&mut self) -> Result<T, Error>
+        async fn read_payload(&mut self, header: &Header) -> Result<Vec<u8>, Error> {
+            let mut payload = vec![0; header.length as usize];
+            self.stream
+                .read_exact(&mut payload)
+                .await
+                .map_err(super::read_error)?;
+            Ok(payload)
+        }
+
+        pub async fn read<T>(&mut self) -> Result<Frame<T>, Error>
         where
             T: serde::de::DeserializeOwned,
         {
-            let mut response = vec![];
-            drop(self.stream.read_to_end(&mut response).await);
-            match serde_json::from_slice(&response) {
-                Ok(response) => Ok(response),
-                Err(_) => Err(Error::Read),
+            let header = self.read_header().await?.check()?;
+            let payload = self.read_payload(&header).await?;
+            super::decode(&header, &payload)
+        }
+
+        pub async fn write<E>(&mut self, id: RequestId, payload: E) -> Result<(), Error>
+        where
+            E: serde::Serialize,
+        {
+            match self.stream.write_all(&super::encode(id, payload)?).await {
+                Ok(_) => Ok(()),
+                Err(_) => Err(Error::Write),
             }
         }
 
-        pub async fn write<E>(&mut self, payload: E) -> Result<(), Error>
+        /// Sends a request with a new id, replies will carry the same id
+        pub async fn send<E>(&mut self, payload: E) -> Result<RequestId, Error>
         where
             E: serde::Serialize,
         {
-            let ans = match self
-                .stream
-                .write_all(&serde_json::to_vec(&payload).unwrap())
-                .await
-            {
+            self.last_id = self.last_id.wrapping_add(1).max(1);
+            self.write(self.last_id, payload).await?;
+            Ok(self.last_id)
+        }
+
+        /// Writes a message as a line of json, without a frame
+        pub async fn write_line<E>(&mut self, payload: E) -> Result<(), Error>
+        where
+            E: serde::Serialize,
+        {
+            match self.stream.write_all(&super::encode_line(payload)?).await {
                 Ok(_) => Ok(()),
                 Err(_) => Err(Error::Write),
-            };
-            drop(self.stream.shutdown(std::net::Shutdown::Write));
-            ans
+            }
+        }
+
+        /// Resolves once the other end closes the connection, anything it sends is discarded
+        pub fn closed(&self) -> impl Future<Output = ()> + 'static {
+            let mut stream = self.stream.clone();
+            async move {
+                let mut buffer = [0; 256];
+                while let Ok(read) = stream.read(&mut buffer).await {
+                    if read == 0 {
+                        break;
+                    }
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_round_trip() {
+        let header = Header {
+            version: PROTOCOL_VERSION,
+            id: 0x01020304,
+            length: 0x0a0b0c0d,
+        };
+        let bytes = header.encode();
+        assert_eq!(bytes, [0, 1, 1, 2, 3, 4, 0x0a, 0x0b, 0x0c, 0x0d]);
+
+        let decoded = Header::decode(&bytes);
+        assert_eq!(decoded.version, header.version);
+        assert_eq!(decoded.id, header.id);
+        assert_eq!(decoded.length, header.length);
+    }
+
+    #[test]
+    fn check_payload_size() {
+        let header = |length| Header {
+            version: PROTOCOL_VERSION,
+            id: 1,
+            length,
+        };
+        assert!(header(MAX_PAYLOAD_SIZE).check().is_ok());
+        assert!(matches!(
+            header(MAX_PAYLOAD_SIZE + 1).check(),
+            Err(Error::PayloadTooLarge(length)) if length == MAX_PAYLOAD_SIZE + 1
+        ));
+    }
+
+    #[test]
+    fn check_version() {
+        let header = Header {
+            version: PROTOCOL_VERSION + 1,
+            id: 1,
+            length: 0,
+        };
+        assert!(matches!(
+            header.check(),
+            Err(Error::Incompatible { local, remote })
+                if local == PROTOCOL_VERSION && remote == PROTOCOL_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let frame = encode(7, vec![format!("a"), format!("b")]).unwrap();
+        let header = Header::decode(frame[..HEADER_SIZE].try_into().unwrap())
+            .check()
+            .unwrap();
+        assert_eq!(header.id, 7);
+        assert_eq!(header.length as usize, frame.len() - HEADER_SIZE);
+
+        let frame: Frame<Vec<String>> = decode(&header, &frame[HEADER_SIZE..]).unwrap();
+        assert_eq!(frame.id, 7);
+        assert_eq!(frame.payload, [format!("a"), format!("b")]);
+    }
+
+    #[test]
+    fn line_is_plain_json() {
+        let line = encode_line(["a"]).unwrap();
+        assert_eq!(line, b"[\"a\"]\n");
+    }
+
+    #[test]
+    fn read_rejects_large_payload() {
+        use std::io::Write;
+
+        let (mut client, server) = std::os::unix::net::UnixStream::pair().unwrap();
+        let header = Header {
+            version: PROTOCOL_VERSION,
+            id: 1,
+            length: u32::MAX,
+        };
+        client.write_all(&header.encode()).unwrap();
+
+        let mut server = sync::UnixStreamWrapper::new(server);
+        assert!(matches!(
+            server.read::<()>(),
+            Err(Error::PayloadTooLarge(u32::MAX))
+        ));
+    }
+}
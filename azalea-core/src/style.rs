@@ -0,0 +1,134 @@
+//! SCSS compilation
+
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+use crate::error::StyleError;
+
+/// Default style, used when no style file is given
+pub const DEFAULT: &str = include_str!("./style.scss");
+
+/// Scss variables declared before every compiled style, e.g. a generated palette
+static VARIABLES: Mutex<String> = Mutex::new(String::new());
+
+static CHANGED: LazyLock<(flume::Sender<()>, flume::Receiver<()>)> =
+    LazyLock::new(flume::unbounded);
+
+/// Replaces the variables declared before compiling styles, and recompiles them
+pub fn set_variables(variables: &[(String, String)]) {
+    // Kept in a single line, so error locations in the style file don't move
+    let scss = variables
+        .iter()
+        .map(|(name, value)| format!("${name}: {value};"))
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    if let Ok(mut current) = VARIABLES.lock() {
+        *current = scss;
+    }
+    notify_changed();
+}
+
+fn variables() -> String {
+    VARIABLES
+        .lock()
+        .map(|variables| variables.clone())
+        .unwrap_or_default()
+}
+
+/// Asks the daemon to recompile its styles
+pub fn notify_changed() {
+    drop(CHANGED.0.send(()));
+}
+
+/// Receives a message whenever styles should be recompiled
+pub fn changed() -> flume::Receiver<()> {
+    CHANGED.1.clone()
+}
+
+/// Compiled css along with every file read to produce it
+pub struct Compiled {
+    pub css: String,
+    pub files: Vec<PathBuf>,
+}
+
+/// Reads from disk, keeping track of which files were read
+#[derive(Debug)]
+struct TrackingFs {
+    entry: PathBuf,
+    variables: String,
+    files: RefCell<Vec<PathBuf>>,
+}
+
+impl grass::Fs for TrackingFs {
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        let mut bytes = std::fs::read(path)?;
+        if path == self.entry && !self.variables.is_empty() {
+            bytes = [format!("{} ", self.variables).into_bytes(), bytes].concat();
+        }
+        let mut files = self.files.borrow_mut();
+        if !files.iter().any(|file| file == path) {
+            files.push(path.to_path_buf());
+        }
+        Ok(bytes)
+    }
+}
+
+pub fn compile_default() -> Result<String, StyleError> {
+    grass::from_string(
+        format!("{}\n{DEFAULT}", variables()),
+        &grass::Options::default(),
+    )
+    .map_err(StyleError::from)
+}
+
+/// Compiles a style file, `@use` and `@import` are resolved against its directory
+pub fn compile_file(path: &Path) -> Result<Compiled, StyleError> {
+    if !path.is_file() {
+        return Err(StyleError::NotFound(path.to_path_buf()));
+    }
+
+    let fs = TrackingFs {
+        entry: path.to_path_buf(),
+        variables: variables(),
+        files: Default::default(),
+    };
+    let mut options = grass::Options::default().fs(&fs);
+    if let Some(dir) = path.parent() {
+        options = options.load_path(dir);
+    }
+
+    let css = grass::from_path(path, &options).map_err(StyleError::from)?;
+
+    Ok(Compiled {
+        css,
+        files: fs.files.take(),
+    })
+}
+
+impl From<Box<grass::Error>> for StyleError {
+    fn from(error: Box<grass::Error>) -> Self {
+        match (*error).kind() {
+            grass::ErrorKind::ParseError { message, loc, .. } => StyleError::Compile {
+                file: PathBuf::from(loc.file.name()),
+                line: loc.begin.line + 1,
+                column: loc.begin.column + 1,
+                message,
+            },
+            grass::ErrorKind::IoError(e) => StyleError::Other(e.to_string()),
+            grass::ErrorKind::FromUtf8Error(e) => StyleError::Other(e),
+            _ => StyleError::Other(format!("Unknown sass error")),
+        }
+    }
+}
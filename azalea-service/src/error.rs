@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use tokio::sync::broadcast;
 
 #[derive(thiserror::Error, Debug)]
@@ -19,3 +21,25 @@ impl From<broadcast::error::RecvError> for Error {
         Self::Failure
     }
 }
+
+/// Why a `Handler::request` got no reply
+#[derive(thiserror::Error, Debug)]
+pub enum RequestError {
+    #[error("Service isn't running")]
+    Stopped,
+    #[error("Service didn't reply within {0:?}")]
+    Timeout(Duration),
+}
+
+/// Why a `Handler::send` didn't queue its input
+#[derive(thiserror::Error, Debug)]
+pub enum SendError {
+    #[error("Service input is full")]
+    Full,
+    #[error("Service isn't running")]
+    Stopped,
+    #[error("Service failed: {0}")]
+    Failed(String),
+    #[error("Service handler is unavailable")]
+    Disconnected,
+}
@@ -1,13 +1,22 @@
 use std::{
     cell::RefCell,
+    panic::AssertUnwindSafe,
     rc::Rc,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use azalea_log as log;
-use tokio::sync::broadcast;
+use futures_lite::FutureExt;
+use tokio::sync::{broadcast, oneshot};
 
-use super::{Service, Status};
+use super::{
+    Channel, Health, Overflow, Service, Status, Supervisor,
+    error::{RequestError, SendError},
+};
 
 pub struct ListenerHandle(
     Arc<broadcast::Sender<()>>,
@@ -59,6 +68,35 @@ impl Drop for LocalListenerHandle {
     }
 }
 
+/// Longest time `Handler::send` waits for room in the input
+const SEND_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Lifecycle of a handled service, shared with its task
+#[derive(Debug)]
+struct State {
+    status: Status,
+    started_at: Option<Instant>,
+    restarts: u32,
+}
+
+/// Query sent to the service, with where to send its reply
+type Request<S> = (
+    <S as Service>::Query,
+    oneshot::Sender<<S as Service>::Reply>,
+);
+
+/// Last output of every key, replayed to new listeners
+#[derive(Clone)]
+struct Replay<S>
+where
+    S: Service,
+{
+    key: fn(&S::Output) -> Option<String>,
+    outputs: Arc<Mutex<Vec<(String, S::Output)>>>,
+    /// Outputs sent by the service, forwarded to the listeners once recorded
+    sender: broadcast::Sender<S::Output>,
+}
+
 /// Service handler responsible for managing/handling a service
 #[derive(Clone)]
 pub struct Handler<S>
@@ -66,10 +104,21 @@ where
     S: Service,
 {
     input: flume::Sender<S::Input>,
+    input_receiver: flume::Receiver<S::Input>,
+    overflow: Overflow,
+    coalesce: Option<fn(&S::Input) -> Option<String>>,
     output: broadcast::Sender<S::Output>,
+    output_capacity: usize,
+    queries: flume::Sender<Request<S>>,
+    query_receiver: flume::Receiver<Request<S>>,
+    request_timeout: Duration,
     cancellation: Arc<broadcast::Sender<()>>,
     init: S::Init,
-    status: Arc<Mutex<Option<flume::Receiver<S::Input>>>>,
+    supervisor: Supervisor,
+    state: Arc<Mutex<State>>,
+    lagged: Arc<AtomicU64>,
+    dropped: Arc<AtomicU64>,
+    replay: Option<Replay<S>>,
 }
 
 impl<S> Handler<S>
@@ -79,49 +128,158 @@ where
     pub fn new(init: S::Init, input_capacity: usize, output_capacity: usize) -> Self {
         let (input_sender, input_receiver) = flume::bounded(input_capacity);
         let (output_sender, _) = broadcast::channel(output_capacity);
+        let (query_sender, query_receiver) = flume::bounded(input_capacity);
         let (cancellation_sender, _) = broadcast::channel(1);
 
         Self {
             input: input_sender,
+            input_receiver,
+            overflow: Overflow::default(),
+            coalesce: None,
             output: output_sender,
+            output_capacity,
+            queries: query_sender,
+            query_receiver,
+            request_timeout: Duration::from_secs(5),
             init,
             cancellation: Arc::new(cancellation_sender),
-            status: Arc::new(Mutex::new(Some(input_receiver))),
+            supervisor: Supervisor::default(),
+            state: Arc::new(Mutex::new(State {
+                status: Status::Stopped,
+                started_at: None,
+                restarts: 0,
+            })),
+            lagged: Arc::new(AtomicU64::new(0)),
+            dropped: Arc::new(AtomicU64::new(0)),
+            replay: None,
         }
     }
 
+    /// Replaces the restart policy used when the service fails
+    pub fn supervisor(mut self, supervisor: Supervisor) -> Self {
+        self.supervisor = supervisor;
+        self
+    }
+
+    /// Replaces what `send` does when the input is full
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Inputs with the same key replace each other when the input is full, for inputs setting
+    /// an absolute value where only the latest one matters. Inputs without a key are kept
+    pub fn coalesce(mut self, key: fn(&S::Input) -> Option<String>) -> Self {
+        self.overflow = Overflow::Coalesce;
+        self.coalesce = Some(key);
+        self
+    }
+
+    /// Replaces how long `request` waits for a reply, 5 seconds by default
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// State mode, the last output of every key is replayed to new listeners so they don't wait
+    /// for the next change, outputs without a key aren't kept
+    pub fn replay(mut self, key: fn(&S::Output) -> Option<String>) -> Self {
+        let (sender, _) = broadcast::channel(self.output_capacity);
+        self.replay = Some(Replay {
+            key,
+            outputs: Default::default(),
+            sender,
+        });
+        self
+    }
+
     fn _start(&mut self, local: bool) {
-        let Some(input) = self.status.lock().unwrap().take() else {
-            return;
-        };
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            if matches!(state.status, Status::Starting | Status::Started) {
+                return;
+            }
+            state.status = Status::Starting;
+            state.restarts = 0;
+        }
 
+        let input = self.input_receiver.clone();
         let input_sender = self.input.clone();
-        let output_sender = self.output.clone();
+        let queries = self.query_receiver.clone();
+        let output_sender = self.record_outputs();
         let init = self.init.clone();
+        let supervisor = self.supervisor.clone();
         let mut cancellation_receiver = self.cancellation.subscribe();
-        let status = self.status.clone();
+        // Not the Arc, its count tracks the listeners
+        let cancellation = (*self.cancellation).clone();
+        let state = self.state.clone();
 
-        let task = async move {
-            let mut service = S::new(init, input_sender, output_sender.clone()).await;
-            let thread_id = std::thread::current().id();
-            log::info!(S, "Service started at thread: {:?}", thread_id);
+        if let Some(observer) = crate::output_observer() {
+            let mut output = self.output.subscribe();
+            let mut cancellation_receiver = self.cancellation.subscribe();
 
+            relm4::spawn(async move {
+                loop {
+                    tokio::select! {
+                        Ok(_) = output.recv() => observer(std::any::type_name::<S>()),
+                        _ = cancellation_receiver.recv() => break,
+                    }
+                }
+            });
+        }
+
+        let task = async move {
             loop {
+                let run = Self::run(
+                    init.clone(),
+                    input.clone(),
+                    input_sender.clone(),
+                    queries.clone(),
+                    output_sender.clone(),
+                    &mut cancellation_receiver,
+                    &state,
+                );
+
+                let reason = match AssertUnwindSafe(run).catch_unwind().await {
+                    Ok(Ok(())) => break,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(panic) => panic
+                        .downcast_ref::<&str>()
+                        .map(|message| message.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or(format!("Service panicked")),
+                };
+
+                let Ok(mut current) = state.lock() else {
+                    break;
+                };
+                if current.restarts >= supervisor.max_retries {
+                    log::warning!(S, "Service failed: {}", reason);
+                    current.status = Status::Failed(reason);
+                    current.started_at = None;
+                    // Stops the output observer
+                    drop(cancellation.send(()));
+                    return;
+                }
+                current.status = Status::Starting;
+                current.started_at = None;
+                current.restarts += 1;
+                let delay = supervisor.delay(current.restarts);
+                drop(current);
+
+                log::warning!(S, "Service failed, restarting in {:?}: {}", delay, reason);
+
                 tokio::select! {
-                    event = service.event_generator(), if !S::DISABLE_EVENTS => {
-                        match service.event_handler(event, &output_sender).await {
-                            Ok(_) => continue,
-                            Err(e) => log::debug!(S, "Service iteration failed {}", e),
-                        }
-                    },
-                    Ok(msg) = input.recv_async() => service.message(msg, &output_sender).await,
+                    _ = tokio::time::sleep(delay) => continue,
                     _ = cancellation_receiver.recv() => break,
-                    else => continue,
-                };
+                }
             }
 
-            if let Ok(mut status) = status.lock() {
-                *status = Some(input);
+            if let Ok(mut state) = state.lock() {
+                state.status = Status::Stopped;
+                state.started_at = None;
             };
 
             log::info!(S, "Service stopped");
@@ -136,6 +294,116 @@ where
         }
     }
 
+    /// Sender given to the service, in state mode its outputs go through a task recording them
+    /// before they reach the listeners
+    fn record_outputs(&self) -> broadcast::Sender<S::Output> {
+        let Some(replay) = &self.replay else {
+            return self.output.clone();
+        };
+
+        // State of a previous run
+        if let Ok(mut outputs) = replay.outputs.lock() {
+            outputs.clear();
+        }
+
+        let key = replay.key;
+        let outputs = replay.outputs.clone();
+        let output = self.output.clone();
+        let mut recorded = replay.sender.subscribe();
+        let mut cancellation_receiver = self.cancellation.subscribe();
+        let lagged = self.lagged.clone();
+
+        relm4::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                tokio::select! {
+                    event = recorded.recv() => {
+                        let event = match event {
+                            Ok(event) => event,
+                            Err(RecvError::Lagged(missed)) => {
+                                log::warning!(
+                                    S,
+                                    "State recorder lagged behind, skipped {} output(s)",
+                                    missed
+                                );
+                                lagged.fetch_add(missed, Ordering::Relaxed);
+                                continue;
+                            }
+                            Err(RecvError::Closed) => break,
+                        };
+                        let Ok(mut outputs) = outputs.lock() else {
+                            break;
+                        };
+                        if let Some(key) = key(&event) {
+                            outputs.retain(|(recorded, _)| *recorded != key);
+                            outputs.push((key, event.clone()));
+                        }
+                        // Sent while locked, so a listener subscribing meanwhile either gets it
+                        // replayed or sent, never both
+                        drop(output.send(event));
+                    },
+                    _ = cancellation_receiver.recv() => break,
+                }
+            }
+        });
+
+        replay.sender.clone()
+    }
+
+    /// Subscribes to the outputs, along with the recorded state in state mode
+    fn subscribe(&self) -> (broadcast::Receiver<S::Output>, Vec<S::Output>) {
+        let outputs = self
+            .replay
+            .as_ref()
+            .and_then(|replay| replay.outputs.lock().ok());
+        let replayed = outputs
+            .as_ref()
+            .map(|outputs| outputs.iter().map(|(_, event)| event.clone()).collect())
+            .unwrap_or_default();
+
+        // Still locked, see `record_outputs`
+        (self.output.subscribe(), replayed)
+    }
+
+    /// Creates and runs the service until it's cancelled (`Ok`) or fails (`Err`)
+    async fn run(
+        init: S::Init,
+        input: flume::Receiver<S::Input>,
+        input_sender: flume::Sender<S::Input>,
+        queries: flume::Receiver<Request<S>>,
+        output_sender: broadcast::Sender<S::Output>,
+        cancellation_receiver: &mut broadcast::Receiver<()>,
+        state: &Mutex<State>,
+    ) -> crate::Result<()> {
+        let mut service = tokio::select! {
+            service = S::try_new(init, input_sender, output_sender.clone()) => service?,
+            _ = cancellation_receiver.recv() => return Ok(()),
+        };
+
+        if let Ok(mut state) = state.lock() {
+            state.status = Status::Started;
+            state.started_at = Some(Instant::now());
+        }
+
+        let thread_id = std::thread::current().id();
+        log::info!(S, "Service started at thread: {:?}", thread_id);
+
+        loop {
+            tokio::select! {
+                event = service.event_generator(), if !S::DISABLE_EVENTS => {
+                    match service.event_handler(event, &output_sender).await {
+                        Ok(_) => continue,
+                        Err(e) => log::debug!(S, "Service iteration failed {}", e),
+                    }
+                },
+                Ok(msg) = input.recv_async() => service.message(msg, &output_sender).await,
+                Ok((query, reply)) = queries.recv_async() => drop(reply.send(service.query(query).await)),
+                _ = cancellation_receiver.recv() => return Ok(()),
+                else => continue,
+            };
+        }
+    }
+
     pub fn start(&mut self) {
         if S::LOCAL {
             self._start(true);
@@ -145,21 +413,165 @@ where
     }
 
     pub fn stop(&mut self) {
-        if self.status.lock().unwrap().is_none() {
+        let running = self
+            .state
+            .lock()
+            .is_ok_and(|state| matches!(state.status, Status::Starting | Status::Started));
+
+        if running {
             drop(self.cancellation.send(()));
         }
     }
 
     pub fn status(&self) -> Status {
-        if self.status.lock().unwrap().is_none() {
-            Status::Started
-        } else {
-            Status::Stopped
+        self.state
+            .lock()
+            .map(|state| state.status.clone())
+            .unwrap_or(Status::Stopped)
+    }
+
+    pub fn health(&self) -> Health {
+        let (status, uptime, restarts) = self
+            .state
+            .lock()
+            .map(|state| {
+                (
+                    state.status.clone(),
+                    state.started_at.map(|started_at| started_at.elapsed()),
+                    state.restarts,
+                )
+            })
+            .unwrap_or((Status::Stopped, None, 0));
+
+        Health {
+            status,
+            uptime,
+            restarts,
+            // Every listener handle holds the cancellation sender, on top of the handler
+            listeners: Arc::strong_count(&self.cancellation).saturating_sub(1),
+            input: Channel {
+                len: self.input.len(),
+                capacity: self.input.capacity(),
+            },
+            output: Channel {
+                len: self.output.len(),
+                capacity: Some(self.output_capacity),
+            },
+            lagged: self.lagged.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
         }
     }
 
-    pub fn send(&mut self, message: S::Input) {
-        drop(self.input.send(message));
+    /// Queues an input, applying the overflow policy when the input is full
+    ///
+    /// Never waits longer than `SEND_TIMEOUT`, as it's called from the GTK thread
+    pub fn send(&mut self, message: S::Input) -> Result<(), SendError> {
+        use flume::TrySendError;
+
+        match self.status() {
+            Status::Failed(reason) => return Err(SendError::Failed(reason)),
+            Status::Stopped => return Err(SendError::Stopped),
+            Status::Starting | Status::Started => {}
+        }
+
+        let message = match self.input.try_send(message) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(message)) => message,
+            Err(TrySendError::Disconnected(_)) => return Err(SendError::Disconnected),
+        };
+
+        match self.overflow {
+            Overflow::Block => self.send_timeout(message),
+            Overflow::Error => {
+                self.count_dropped(1);
+                Err(SendError::Full)
+            }
+            Overflow::DropOldest => {
+                let mut message = message;
+                loop {
+                    if self.input_receiver.try_recv().is_ok() {
+                        self.count_dropped(1);
+                    }
+                    message = match self.input.try_send(message) {
+                        Ok(()) => return Ok(()),
+                        Err(TrySendError::Full(message)) => message,
+                        Err(TrySendError::Disconnected(_)) => return Err(SendError::Disconnected),
+                    };
+                }
+            }
+            Overflow::Coalesce => {
+                let coalesce = self.coalesce;
+                let key = coalesce.and_then(|key| key(&message));
+
+                if key.is_some() {
+                    let queued: Vec<S::Input> = self.input_receiver.try_iter().collect();
+                    let count = queued.len();
+                    let kept: Vec<S::Input> = queued
+                        .into_iter()
+                        .filter(|queued| coalesce.and_then(|key| key(queued)) != key)
+                        .collect();
+                    self.count_dropped(count - kept.len());
+
+                    for queued in kept {
+                        // Waits if another sender took the room meanwhile
+                        self.send_timeout(queued)?;
+                    }
+                }
+
+                self.send_timeout(message)
+            }
+        }
+    }
+
+    /// Waits for the service to make room, the input is dropped after `SEND_TIMEOUT`
+    fn send_timeout(&self, message: S::Input) -> Result<(), SendError> {
+        match self.input.send_timeout(message, SEND_TIMEOUT) {
+            Ok(()) => Ok(()),
+            Err(flume::SendTimeoutError::Timeout(_)) => {
+                self.count_dropped(1);
+                Err(SendError::Full)
+            }
+            Err(flume::SendTimeoutError::Disconnected(_)) => Err(SendError::Disconnected),
+        }
+    }
+
+    fn count_dropped(&self, count: usize) {
+        if count > 0 {
+            log::debug!(S, "Input full, dropped {} input(s)", count);
+            self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Sends a query to the running service and waits for its reply
+    ///
+    /// Fails if the service isn't started (or stops before replying), or when the reply takes
+    /// longer than the request timeout
+    pub fn request(
+        &self,
+        query: S::Query,
+    ) -> impl Future<Output = Result<S::Reply, RequestError>> + Send + 'static {
+        let running = matches!(self.status(), Status::Starting | Status::Started);
+        let queries = self.queries.clone();
+        let timeout = self.request_timeout;
+
+        async move {
+            if !running {
+                return Err(RequestError::Stopped);
+            }
+
+            let (reply_sender, reply) = oneshot::channel();
+            let request = async move {
+                queries
+                    .send_async((query, reply_sender))
+                    .await
+                    .map_err(|_| RequestError::Stopped)?;
+                reply.await.map_err(|_| RequestError::Stopped)
+            };
+
+            tokio::time::timeout(timeout, request)
+                .await
+                .map_err(|_| RequestError::Timeout(timeout))?
+        }
     }
 
     pub fn listen<F: (Fn(S::Output) -> bool) + Send + 'static>(
@@ -168,16 +580,30 @@ where
     ) -> ListenerHandle {
         self.start();
 
-        let mut output = self.output.subscribe();
+        let (mut output, replayed) = self.subscribe();
+        let lagged = self.lagged.clone();
 
         ListenerHandle(
             self.cancellation.clone(),
             Some(relm4::spawn(async move {
                 use tokio::sync::broadcast::error::RecvError;
+                for event in replayed {
+                    if !transform(event) {
+                        return;
+                    }
+                }
                 loop {
                     if match output.recv().await {
                         Err(RecvError::Closed) => false,
-                        Err(RecvError::Lagged(_)) => true,
+                        Err(RecvError::Lagged(missed)) => {
+                            log::warning!(
+                                S,
+                                "Listener lagged behind, skipped {} output(s)",
+                                missed
+                            );
+                            lagged.fetch_add(missed, Ordering::Relaxed);
+                            true
+                        }
                         Ok(event) => transform(event),
                     } {
                         continue;
@@ -214,16 +640,30 @@ where
     ) -> LocalListenerHandle {
         self.start();
 
-        let mut output = self.output.subscribe();
+        let (mut output, replayed) = self.subscribe();
+        let lagged = self.lagged.clone();
 
         LocalListenerHandle(
             self.cancellation.clone(),
             Some(relm4::spawn_local(async move {
                 use tokio::sync::broadcast::error::RecvError;
+                for event in replayed {
+                    if !transform(event) {
+                        return;
+                    }
+                }
                 loop {
                     if match output.recv().await {
                         Err(RecvError::Closed) => false,
-                        Err(RecvError::Lagged(_)) => true,
+                        Err(RecvError::Lagged(missed)) => {
+                            log::warning!(
+                                S,
+                                "Listener lagged behind, skipped {} output(s)",
+                                missed
+                            );
+                            lagged.fetch_add(missed, Ordering::Relaxed);
+                            true
+                        }
                         Ok(event) => transform(event),
                     } {
                         continue;
@@ -280,10 +720,16 @@ where
         Self::static_handler().borrow().status()
     }
 
-    fn send(message: Self::Input) {
+    fn send(message: Self::Input) -> Result<(), SendError> {
         Self::static_handler().borrow_mut().send(message)
     }
 
+    fn request(
+        query: Self::Query,
+    ) -> impl Future<Output = Result<Self::Reply, RequestError>> + Send + 'static {
+        Self::static_handler().borrow().request(query)
+    }
+
     fn listen<F: (Fn(Self::Output) -> bool) + Send + 'static>(
         transform: F,
     ) -> crate::ListenerHandle {
@@ -363,6 +809,17 @@ where
         }
     }
 
+    /// Stops the service and starts it again once it has stopped
+    fn restart() {
+        Self::stop();
+        relm4::spawn(async {
+            while matches!(Self::status(), Status::Starting | Status::Started) {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Self::start();
+        });
+    }
+
     fn status() -> crate::Status {
         match Self::static_handler().lock() {
             Ok(handler) => handler.status(),
@@ -373,10 +830,48 @@ where
         }
     }
 
-    fn send(message: Self::Input) {
+    fn health() -> crate::Health {
         match Self::static_handler().lock() {
+            Ok(handler) => handler.health(),
+            // Only reads the state, a panic while it was locked doesn't matter here
+            Err(e) => e.get_ref().health(),
+        }
+    }
+
+    /// Queues an input, failures are logged too, see `Handler::send`
+    fn send(message: Self::Input) -> Result<(), SendError> {
+        let result = match Self::static_handler().lock() {
             Ok(mut handler) => handler.send(message),
-            Err(e) => azalea_log::warning!(Self, "Failed to lock service handler: {}", e),
+            Err(e) => {
+                azalea_log::warning!(Self, "Failed to lock service handler: {}", e);
+                Err(SendError::Disconnected)
+            }
+        };
+
+        // Callers warn with context where a user is waiting on it
+        if let Err(e) = &result {
+            azalea_log::debug!(Self, "Failed to send input: {}", e);
+        }
+        result
+    }
+
+    /// Sends a query to the service and waits for its reply, see `Handler::request`
+    fn request(
+        query: Self::Query,
+    ) -> impl Future<Output = Result<Self::Reply, RequestError>> + Send + 'static {
+        let request = match Self::static_handler().lock() {
+            Ok(handler) => Some(handler.request(query)),
+            Err(e) => {
+                azalea_log::warning!(Self, "Failed to lock service handler: {}", e);
+                None
+            }
+        };
+
+        async move {
+            match request {
+                Some(request) => request.await,
+                None => Err(RequestError::Stopped),
+            }
         }
     }
 
@@ -482,5 +977,149 @@ macro_rules! impl_static_handler {
                 HANDLER.clone()
             }
         }
+
+        $crate::registry::inventory::submit! {
+            $crate::registry::Entry::of::<$service>(concat!(
+                module_path!(),
+                "::",
+                stringify!($service)
+            ))
+        }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter;
+
+    #[derive(Debug, PartialEq)]
+    enum Input {
+        Set(u32),
+        Adjust(i32),
+    }
+
+    impl Service for Counter {
+        type Init = ();
+        type Input = Input;
+        type Event = ();
+        type Output = ();
+        type Query = ();
+        type Reply = ();
+
+        async fn try_new(
+            _: Self::Init,
+            _: flume::Sender<Self::Input>,
+            _: broadcast::Sender<Self::Output>,
+        ) -> crate::Result<Self> {
+            Ok(Self)
+        }
+    }
+
+    /// Handler marked as started without running the service, inputs stay queued
+    fn started(capacity: usize) -> Handler<Counter> {
+        let handler = Handler::new((), capacity, 1);
+        handler.state.lock().unwrap().status = Status::Started;
+        handler
+    }
+
+    fn queued(handler: &Handler<Counter>) -> Vec<Input> {
+        handler.input_receiver.try_iter().collect()
+    }
+
+    #[test]
+    fn overflow_error() {
+        let mut handler = started(1).overflow(Overflow::Error);
+        handler.send(Input::Set(1)).unwrap();
+        assert!(matches!(handler.send(Input::Set(2)), Err(SendError::Full)));
+        assert_eq!(handler.health().dropped, 1);
+        assert_eq!(queued(&handler), [Input::Set(1)]);
+    }
+
+    #[test]
+    fn overflow_drop_oldest() {
+        let mut handler = started(2).overflow(Overflow::DropOldest);
+        handler.send(Input::Set(1)).unwrap();
+        handler.send(Input::Set(2)).unwrap();
+        handler.send(Input::Set(3)).unwrap();
+        assert_eq!(handler.health().dropped, 1);
+        assert_eq!(queued(&handler), [Input::Set(2), Input::Set(3)]);
+    }
+
+    #[test]
+    fn coalesce_replaces_same_key() {
+        let mut handler = started(2).coalesce(|input| match input {
+            Input::Set(_) => Some(format!("set")),
+            Input::Adjust(_) => None,
+        });
+        handler.send(Input::Set(1)).unwrap();
+        handler.send(Input::Adjust(-1)).unwrap();
+        handler.send(Input::Set(2)).unwrap();
+        assert_eq!(handler.health().dropped, 1);
+        assert_eq!(queued(&handler), [Input::Adjust(-1), Input::Set(2)]);
+    }
+
+    #[test]
+    fn coalesce_keeps_other_keys() {
+        let mut handler = started(2).coalesce(|input| match input {
+            Input::Set(value) => Some(format!("{}", value % 2)),
+            Input::Adjust(_) => None,
+        });
+        handler.send(Input::Set(1)).unwrap();
+        handler.send(Input::Set(2)).unwrap();
+        handler.send(Input::Set(4)).unwrap();
+        assert_eq!(handler.health().dropped, 1);
+        assert_eq!(queued(&handler), [Input::Set(1), Input::Set(4)]);
+    }
+
+    #[test]
+    fn coalesce_never_drops_unkeyed() {
+        let mut handler = started(1).coalesce(|input| match input {
+            Input::Set(_) => Some(format!("set")),
+            Input::Adjust(_) => None,
+        });
+        handler.send(Input::Adjust(1)).unwrap();
+        assert!(matches!(
+            handler.send(Input::Adjust(2)),
+            Err(SendError::Full)
+        ));
+        assert_eq!(handler.health().dropped, 1);
+        assert_eq!(queued(&handler), [Input::Adjust(1)]);
+    }
+
+    #[test]
+    fn block_waits_for_room() {
+        let mut handler = started(1);
+        handler.send(Input::Set(1)).unwrap();
+
+        let receiver = handler.input_receiver.clone();
+        let consumer = std::thread::spawn(move || {
+            std::thread::sleep(SEND_TIMEOUT / 4);
+            receiver.recv().unwrap()
+        });
+        handler.send(Input::Set(2)).unwrap();
+
+        assert_eq!(consumer.join().unwrap(), Input::Set(1));
+        assert_eq!(queued(&handler), [Input::Set(2)]);
+    }
+
+    #[test]
+    fn block_gives_up() {
+        let mut handler = started(1);
+        handler.send(Input::Set(1)).unwrap();
+        assert!(matches!(handler.send(Input::Set(2)), Err(SendError::Full)));
+        assert_eq!(handler.health().dropped, 1);
+        assert_eq!(queued(&handler), [Input::Set(1)]);
+    }
+
+    #[test]
+    fn send_to_stopped() {
+        let mut handler = Handler::<Counter>::new((), 1, 1);
+        assert!(matches!(
+            handler.send(Input::Set(1)),
+            Err(SendError::Stopped)
+        ));
+        assert!(queued(&handler).is_empty());
+    }
+}
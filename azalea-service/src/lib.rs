@@ -5,10 +5,14 @@
 mod handler;
 pub use handler::*;
 
+mod observer;
+pub use observer::*;
+
 mod status;
 pub use status::*;
 
 pub mod error;
+pub mod registry;
 
 use tokio::sync::broadcast;
 
@@ -21,14 +25,33 @@ where
     type Input: Send;
     type Event: Send;
     type Output: Clone + 'static + Send;
+    /// Requests answered by `query`, use `()` if the service has none
+    type Query: Send;
+    type Reply: Send;
     const DISABLE_EVENTS: bool = false;
     const LOCAL: bool = false;
 
+    /// Creates the service, errors are reported by the handler and the service is restarted
+    /// according to its `Supervisor`
+    fn try_new(
+        init: Self::Init,
+        input_sender: flume::Sender<Self::Input>,
+        output_sender: broadcast::Sender<Self::Output>,
+    ) -> impl std::future::Future<Output = Result<Self>> + Send;
+
+    /// Infallible version of `try_new`, panics if the service can't be created
     fn new(
         init: Self::Init,
         input_sender: flume::Sender<Self::Input>,
         output_sender: broadcast::Sender<Self::Output>,
-    ) -> impl std::future::Future<Output = Self> + Send;
+    ) -> impl std::future::Future<Output = Self> + Send {
+        async move {
+            match Self::try_new(init, input_sender, output_sender).await {
+                Ok(service) => service,
+                Err(e) => azalea_log::error!(Self, "Failed to create service: {}", e),
+            }
+        }
+    }
 
     fn handler(init: Self::Init) -> Handler<Self> {
         Handler::new(init, 1, 1)
@@ -42,6 +65,16 @@ where
         async {}
     }
 
+    /// Answers a request sent with `Handler::request`
+    fn query(
+        &mut self,
+        _query: Self::Query,
+    ) -> impl std::future::Future<Output = Self::Reply> + Send {
+        async {
+            azalea_log::error!(Self, "Query handler not implemented!");
+        }
+    }
+
     fn event_generator(&mut self) -> impl std::future::Future<Output = Self::Event> + Send {
         async {
             azalea_log::error!(Self, "Event generator not implemented!");
@@ -0,0 +1,22 @@
+use std::sync::OnceLock;
+
+/// Callback receiving the service type name whenever it emits an output
+pub type OutputObserver = Box<dyn Fn(&'static str) + Send + Sync>;
+
+static OUTPUT_OBSERVER: OnceLock<OutputObserver> = OnceLock::new();
+
+/// Registers a global observer for the outputs of every started service
+///
+/// Only the first observer is kept, useful for notifying external tools
+pub fn observe_outputs<F>(observer: F)
+where
+    F: Fn(&'static str) + Send + Sync + 'static,
+{
+    if OUTPUT_OBSERVER.set(Box::new(observer)).is_err() {
+        azalea_log::warning!("Service output observer already registered");
+    }
+}
+
+pub(crate) fn output_observer() -> Option<&'static OutputObserver> {
+    OUTPUT_OBSERVER.get()
+}
@@ -0,0 +1,68 @@
+//! Every static handler, registered by `impl_static_handler!` so services can be controlled
+//! before being used
+
+use crate::{Health, StaticHandler};
+
+#[doc(hidden)]
+pub use inventory;
+
+/// Type erased controls of a static handler
+#[derive(Clone, Copy)]
+pub struct Entry {
+    /// Service type name
+    pub name: &'static str,
+    pub health: fn() -> Health,
+    pub start: fn(),
+    pub stop: fn(),
+    pub restart: fn(),
+}
+
+impl Entry {
+    /// Controls of the static handler of `S`, `name` being its type name
+    pub const fn of<S: StaticHandler>(name: &'static str) -> Self {
+        Self {
+            name,
+            health: S::health,
+            start: S::start,
+            stop: S::stop,
+            restart: S::restart,
+        }
+    }
+
+    /// Type name without the crate, module prefix and `::Service` suffix, e.g. `dbus::mpris`
+    pub fn short_name(&self) -> &'static str {
+        let name = self.name.strip_suffix("::Service").unwrap_or(self.name);
+        name.split_once("service::")
+            .map(|(_, name)| name)
+            .unwrap_or(name)
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let short_name = self.short_name();
+        self.name == name
+            || short_name == name
+            || short_name
+                .rsplit_once("::")
+                .is_some_and(|(_, last)| last == name)
+    }
+}
+
+inventory::collect!(Entry);
+
+/// Registered services, sorted by name
+pub fn entries() -> Vec<Entry> {
+    let mut entries: Vec<Entry> = inventory::iter::<Entry>.into_iter().copied().collect();
+    entries.sort_by_key(|entry| entry.short_name());
+    entries
+}
+
+/// Finds a service by its type name, short name or the last segment of it (`mpris`)
+pub fn find(name: &str) -> Result<Entry, String> {
+    let mut matches = entries().into_iter().filter(|entry| entry.matches(name));
+
+    match (matches.next(), matches.next()) {
+        (Some(entry), None) => Ok(entry),
+        (Some(_), Some(_)) => Err(format!("Ambiguous service name: {name}")),
+        (None, _) => Err(format!("Service not found: {name}")),
+    }
+}
@@ -1,5 +1,117 @@
-#[derive(Clone)]
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Status {
+    /// Being created, or waiting to be restarted after a failure
+    Starting,
     Started,
     Stopped,
+    /// Gave up restarting, with the reason of the last failure
+    Failed(String),
+}
+
+/// How a handler restarts a service after it fails or panics
+#[derive(Clone, Debug)]
+pub struct Supervisor {
+    pub max_retries: u32,
+    /// Delay before the first restart, doubled after every failed one
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Supervisor {
+    /// Never restarts the service
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn delay(&self, restart: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(restart.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// What `send` does when the input channel of a service is full
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Overflow {
+    /// Waits briefly for the service to make room, then rejects the new input
+    #[default]
+    Block,
+    /// Drops the oldest queued input
+    DropOldest,
+    /// Replaces the queued inputs with the same key as the new one, see `Handler::coalesce`.
+    /// Waits like `Block` when there's none, queued inputs are never dropped
+    Coalesce,
+    /// Rejects the new input
+    Error,
+}
+
+/// Snapshot of a handled service, reported by `azalea services list`
+#[derive(Clone, Debug)]
+pub struct Health {
+    pub status: Status,
+    /// Time since the service last started, `None` unless it's running
+    pub uptime: Option<Duration>,
+    /// Restarts since it was started, reset by a manual start
+    pub restarts: u32,
+    pub listeners: usize,
+    pub input: Channel,
+    pub output: Channel,
+    /// Outputs missed by listeners that fell behind
+    pub lagged: u64,
+    /// Inputs dropped by the overflow policy
+    pub dropped: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Channel {
+    /// Queued messages
+    pub len: usize,
+    pub capacity: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles() {
+        let supervisor = Supervisor::default();
+        assert_eq!(supervisor.delay(1), Duration::from_secs(1));
+        assert_eq!(supervisor.delay(2), Duration::from_secs(2));
+        assert_eq!(supervisor.delay(3), Duration::from_secs(4));
+        assert_eq!(supervisor.delay(6), Duration::from_secs(32));
+    }
+
+    #[test]
+    fn delay_capped() {
+        let supervisor = Supervisor::default();
+        assert_eq!(supervisor.delay(7), Duration::from_secs(60));
+        assert_eq!(supervisor.delay(40), Duration::from_secs(60));
+        assert_eq!(supervisor.delay(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn delay_first_restart() {
+        let supervisor = Supervisor {
+            backoff: Duration::from_millis(250),
+            ..Default::default()
+        };
+        assert_eq!(supervisor.delay(0), Duration::from_millis(250));
+        assert_eq!(supervisor.delay(1), Duration::from_millis(250));
+    }
 }
@@ -0,0 +1,90 @@
+//! Prints the color scheme changes
+//!
+//! With `--stub`, a fake settings portal cycling through every color scheme is served first, run
+//! it on a private session bus so it doesn't clash with the real portal:
+//!
+//! `dbus-run-session -- cargo run --bin appearance -- --stub`
+
+use azalea_service::StaticHandler;
+use azalea_shell::service::{self, dbus::appearance};
+use zbus::{object_server::SignalEmitter, zvariant::OwnedValue};
+
+const PATH: &str = "/org/freedesktop/portal/desktop";
+
+struct StubSettings {
+    color_scheme: u32,
+}
+
+#[zbus::interface(name = "org.freedesktop.portal.Settings")]
+impl StubSettings {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::fdo::Result<OwnedValue> {
+        if namespace == appearance::NAMESPACE && key == appearance::KEY {
+            Ok(OwnedValue::from(self.color_scheme))
+        } else {
+            Err(zbus::fdo::Error::Failed(format!(
+                "Unknown setting: {namespace} {key}"
+            )))
+        }
+    }
+
+    #[zbus(signal)]
+    async fn setting_changed(
+        emitter: &SignalEmitter<'_>,
+        namespace: &str,
+        key: &str,
+        value: zbus::zvariant::Value<'_>,
+    ) -> zbus::Result<()>;
+}
+
+async fn serve_stub() -> zbus::Result<()> {
+    let connection = zbus::conn::Builder::session()?
+        .name("org.freedesktop.portal.Desktop")?
+        .serve_at(PATH, StubSettings { color_scheme: 0 })?
+        .build()
+        .await?;
+
+    let settings = connection
+        .object_server()
+        .interface::<_, StubSettings>(PATH)
+        .await?;
+
+    tokio::spawn(async move {
+        // Keeps the portal name owned
+        let _connection = connection;
+
+        for color_scheme in [1u32, 2, 0].into_iter().cycle() {
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            settings.get_mut().await.color_scheme = color_scheme;
+
+            drop(
+                StubSettings::setting_changed(
+                    settings.signal_emitter(),
+                    appearance::NAMESPACE,
+                    appearance::KEY,
+                    color_scheme.into(),
+                )
+                .await,
+            );
+        }
+    });
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    if std::env::args().any(|arg| arg == "--stub") {
+        serve_stub().await.unwrap();
+    }
+
+    service::dbus::appearance::Service::init(service::dbus::appearance::Init {
+        dbus_connection: Some(zbus::Connection::session().await.unwrap()),
+    });
+
+    service::dbus::appearance::Service::listen(|out| {
+        azalea_log::message!("APPEARANCE output received:\n{out:#?}");
+        true
+    })
+    .join()
+    .await;
+}
@@ -0,0 +1,202 @@
+//! Image caches, decoded pixbufs are kept in memory and fetched images on disk
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+use gtk::{gdk_pixbuf, glib};
+
+/// In-memory LRU cache, bounded by the size of the decoded pixels
+pub struct Memory {
+    entries: HashMap<String, Entry>,
+    size: usize,
+    capacity: usize,
+}
+
+struct Entry {
+    pixbuf: gdk_pixbuf::Pixbuf,
+    size: usize,
+    accessed: Instant,
+}
+
+impl Memory {
+    /// 64 MiB, a 512x512 album art takes 1 MiB
+    pub const CAPACITY: usize = 64 * 1024 * 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Default::default(),
+            size: 0,
+            capacity,
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<gdk_pixbuf::Pixbuf> {
+        let entry = self.entries.get_mut(key)?;
+        entry.accessed = Instant::now();
+        Some(entry.pixbuf.clone())
+    }
+
+    /// Inserts a pixbuf, evicting the least recently used ones until it fits
+    pub fn insert(&mut self, key: String, pixbuf: gdk_pixbuf::Pixbuf) {
+        let size = pixbuf.rowstride() as usize * pixbuf.height() as usize;
+        if size > self.capacity {
+            return;
+        }
+
+        self.remove(&key);
+
+        while self.size + size > self.capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.accessed)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+
+        self.size += size;
+        self.entries.insert(
+            key,
+            Entry {
+                pixbuf,
+                size,
+                accessed: Instant::now(),
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.size -= entry.size;
+        }
+    }
+}
+
+/// On-disk cache for fetched images, so they survive restarts
+pub struct Disk {
+    directory: PathBuf,
+    expiry: Duration,
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Self {
+            directory: glib::user_cache_dir().join("azalea").join("images"),
+            expiry: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+impl Disk {
+    fn path(&self, url: &str) -> Option<PathBuf> {
+        glib::compute_checksum_for_string(glib::ChecksumType::Sha256, url)
+            .map(|hash| self.directory.join(hash.as_str()))
+    }
+
+    /// Reads a cached image, expired ones are removed
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let path = self.path(url)?;
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age > self.expiry {
+            drop(std::fs::remove_file(&path));
+            return None;
+        }
+
+        std::fs::read(&path).ok()
+    }
+
+    pub fn insert(&self, url: &str, data: &[u8]) {
+        let Some(path) = self.path(url) else {
+            return;
+        };
+
+        // Written next to the final file first, so readers never see a partial image
+        let temporary = path.with_extension("part");
+        let result = std::fs::create_dir_all(&self.directory)
+            .and_then(|_| std::fs::write(&temporary, data))
+            .and_then(|_| std::fs::rename(&temporary, &path));
+
+        if let Err(e) = result {
+            azalea_log::warning!("Failed to cache image {url}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16x16 RGBA, takes 1 KiB
+    const SIZE: usize = 16 * 16 * 4;
+
+    fn pixbuf() -> gdk_pixbuf::Pixbuf {
+        gdk_pixbuf::Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, true, 8, 16, 16).unwrap()
+    }
+
+    /// Keeps access times apart, so the least recently used entry is unambiguous
+    fn insert(memory: &mut Memory, key: &str) {
+        std::thread::sleep(Duration::from_millis(1));
+        memory.insert(key.to_string(), pixbuf());
+    }
+
+    #[test]
+    fn memory_evicts_least_recently_used() {
+        let mut memory = Memory::new(3 * SIZE);
+        insert(&mut memory, "a");
+        insert(&mut memory, "b");
+        insert(&mut memory, "c");
+
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(memory.get("a").is_some());
+
+        insert(&mut memory, "d");
+        assert!(memory.get("b").is_none());
+        assert!(memory.get("a").is_some());
+        assert!(memory.get("c").is_some());
+        assert!(memory.get("d").is_some());
+        assert_eq!(memory.size, 3 * SIZE);
+    }
+
+    #[test]
+    fn memory_evicts_until_it_fits() {
+        let mut memory = Memory::new(3 * SIZE);
+        insert(&mut memory, "a");
+        insert(&mut memory, "b");
+        insert(&mut memory, "c");
+
+        std::thread::sleep(Duration::from_millis(1));
+        let large = gdk_pixbuf::Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, true, 8, 16, 32).unwrap();
+        memory.insert(format!("large"), large);
+        assert!(memory.get("a").is_none());
+        assert!(memory.get("b").is_none());
+        assert!(memory.get("c").is_some());
+        assert_eq!(memory.size, 3 * SIZE);
+    }
+
+    #[test]
+    fn memory_replaces_key() {
+        let mut memory = Memory::new(3 * SIZE);
+        insert(&mut memory, "a");
+        insert(&mut memory, "a");
+        assert_eq!(memory.entries.len(), 1);
+        assert_eq!(memory.size, SIZE);
+    }
+
+    #[test]
+    fn memory_skips_larger_than_capacity() {
+        let mut memory = Memory::new(SIZE - 1);
+        insert(&mut memory, "a");
+        assert!(memory.get("a").is_none());
+        assert_eq!(memory.size, 0);
+    }
+}
@@ -0,0 +1,411 @@
+mod cache;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use base64::Engine;
+use relm4::gtk::prelude::{FrameExt, WidgetExt};
+use relm4::gtk::{gdk, gdk_pixbuf};
+use relm4::{Component, ComponentParts, ComponentSender, RelmWidgetExt, component};
+
+pub struct Model {
+    fallback: Option<gdk::Texture>,
+    image: Option<gdk::Texture>,
+    failed: bool,
+    width: Option<i32>,
+    height: Option<i32>,
+    /// Image being fetched and decoded, aborted when another one is requested
+    load: Option<tokio::task::JoinHandle<()>>,
+    /// Bumped on every request, results from older loads are discarded
+    generation: u64,
+}
+
+pub struct Init {
+    pub fallback: Option<gdk::Texture>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Unload,
+    LoadImage(String),
+    LoadPixbuf(gdk::gdk_pixbuf::Pixbuf),
+    LoadTexture(gdk::Texture),
+    LoadBytes(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub enum CommandOutput {
+    Decoded {
+        generation: u64,
+        /// Cache key, only images loaded from an url are cached
+        url: Option<String>,
+        image: Decoded,
+    },
+    Failed {
+        generation: u64,
+        source: String,
+        error: String,
+    },
+}
+
+/// Image file read by `load_image`, not decoded yet
+pub(crate) struct Data {
+    bytes: VecDeque<u8>,
+    // Url of a download, it's only cached on disk once it decodes, so error pages and
+    // truncated downloads aren't served from disk forever
+    fetched: Option<String>,
+}
+
+impl Data {
+    /// Decodes the image, call it from a blocking thread
+    pub(crate) fn into_pixbuf(self) -> Result<gdk_pixbuf::Pixbuf, gdk_pixbuf::glib::Error> {
+        let Some(url) = self.fetched else {
+            return gdk_pixbuf::Pixbuf::from_read(self.bytes);
+        };
+
+        let bytes = Vec::from(self.bytes);
+        let pixbuf = gdk_pixbuf::Pixbuf::from_read(std::io::Cursor::new(bytes.clone()))?;
+        cache::Disk::default().insert(&url, &bytes);
+        Ok(pixbuf)
+    }
+}
+
+/// Decoded pixels, pixbufs are rebuilt from them on the GTK thread
+#[derive(Debug)]
+pub struct Decoded {
+    bytes: gtk::glib::Bytes,
+    has_alpha: bool,
+    bits_per_sample: i32,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+}
+
+impl Decoded {
+    fn new(pixbuf: &gdk_pixbuf::Pixbuf) -> Self {
+        Self {
+            bytes: pixbuf.read_pixel_bytes(),
+            has_alpha: pixbuf.has_alpha(),
+            bits_per_sample: pixbuf.bits_per_sample(),
+            width: pixbuf.width(),
+            height: pixbuf.height(),
+            rowstride: pixbuf.rowstride(),
+        }
+    }
+
+    pub(crate) fn pixbuf(&self) -> gdk_pixbuf::Pixbuf {
+        gdk_pixbuf::Pixbuf::from_bytes(
+            &self.bytes,
+            gdk_pixbuf::Colorspace::Rgb,
+            self.has_alpha,
+            self.bits_per_sample,
+            self.width,
+            self.height,
+            self.rowstride,
+        )
+    }
+}
+
+#[component(pub)]
+impl Component for Model {
+    type CommandOutput = CommandOutput;
+    type Input = Input;
+    type Output = ();
+    type Init = Init;
+
+    view! {
+        gtk::Frame {
+            inline_css: "border-radius: 6px;",
+
+            #[wrap(Some)]
+            set_child = if model.image.is_none() && model.fallback.is_none() && model.failed {
+                gtk::Image {
+                    set_icon_name: Some("image-missing"),
+                    set_halign: gtk::Align::Center,
+                    set_valign: gtk::Align::Center,
+                }
+            } else if model.image.is_none() && model.fallback.is_none() {
+                gtk::Spinner {
+                    set_halign: gtk::Align::Center,
+                    set_valign: gtk::Align::Center,
+                    start: (),
+                }
+            } else {
+                gtk::Picture {
+                    #[watch]
+                    set_paintable: if model.image.is_some() {
+                        model.image.as_ref()
+                    } else {
+                        model.fallback.as_ref()
+                    },
+                    set_can_shrink: true,
+                }
+            },
+
+            set_width_request: model.width.unwrap_or(-1),
+            set_height_request: model.height.unwrap_or(-1),
+        },
+    }
+
+    fn init(
+        init: Self::Init,
+        _root: Self::Root,
+        _sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            fallback: init.fallback,
+            image: None,
+            failed: false,
+            width: init.width,
+            height: init.height,
+            load: None,
+            generation: 0,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, input: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
+        self.cancel();
+
+        match input {
+            Input::LoadImage(url) => {
+                let cached = Self::cache().borrow_mut().get(&url);
+                if let Some(pixbuf) = cached {
+                    azalea_log::debug!(
+                        Self,
+                        "Loaded image (cache hit): {}...",
+                        Self::truncate(&url)
+                    );
+                    self.set_image(&pixbuf);
+                } else {
+                    let (width, height) = (self.width, self.height);
+                    self.spawn_load(&sender, async move {
+                        let Some(data) = Self::load_image(&url).await else {
+                            return Err((url, String::from("Failed to fetch image")));
+                        };
+                        azalea_log::debug!(
+                            Self,
+                            "Loaded image (cache miss): {}...",
+                            Self::truncate(&url)
+                        );
+                        match Self::decode(data, width, height).await {
+                            Ok(image) => Ok((Some(url), image)),
+                            Err(error) => Err((url, error)),
+                        }
+                    });
+                }
+            }
+            Input::LoadPixbuf(pixbuf) => {
+                self.set_image(&resize_pixbuf(pixbuf, self.width, self.height))
+            }
+            Input::LoadTexture(texture) => self.set_image_from_texture(texture),
+            Input::LoadBytes(bytes) => {
+                let (width, height) = (self.width, self.height);
+                self.spawn_load(&sender, async move {
+                    let data = Data {
+                        bytes: bytes.into(),
+                        fetched: None,
+                    };
+                    match Self::decode(data, width, height).await {
+                        Ok(image) => Ok((None, image)),
+                        Err(error) => Err((String::from("bytes"), error)),
+                    }
+                });
+            }
+            Input::Unload => self.set_spinner(),
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            CommandOutput::Decoded {
+                generation,
+                url,
+                image,
+            } if generation == self.generation => {
+                self.load = None;
+                let pixbuf = image.pixbuf();
+                self.set_image(&pixbuf);
+                if let Some(url) = url {
+                    Self::cache().borrow_mut().insert(url, pixbuf);
+                }
+            }
+            CommandOutput::Failed {
+                generation,
+                source,
+                error,
+            } if generation == self.generation => {
+                self.load = None;
+                azalea_log::warning!("Failed to load image {}: {error}", Self::truncate(&source));
+                self.image = None;
+                self.failed = true;
+            }
+            // Finished after a newer image was requested
+            _ => {}
+        }
+    }
+}
+
+impl Drop for Model {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl Model {
+    fn set_spinner(&mut self) {
+        self.image = None;
+        self.failed = false;
+    }
+
+    fn set_image(&mut self, pixbuf: &gdk_pixbuf::Pixbuf) {
+        self.image = Some(gdk::Texture::for_pixbuf(&pixbuf));
+        self.failed = false;
+    }
+
+    fn set_image_from_texture(&mut self, texture: gdk::Texture) {
+        self.image = Some(texture);
+        self.failed = false;
+    }
+
+    /// Aborts the pending load, a result already on its way is discarded by its generation
+    fn cancel(&mut self) {
+        self.generation += 1;
+        if let Some(load) = self.load.take() {
+            load.abort();
+        }
+    }
+
+    fn spawn_load<F>(&mut self, sender: &ComponentSender<Self>, load: F)
+    where
+        F: Future<Output = Result<(Option<String>, Decoded), (String, String)>> + Send + 'static,
+    {
+        let generation = self.generation;
+        let command_sender = sender.command_sender().clone();
+
+        self.load = Some(relm4::spawn(async move {
+            drop(command_sender.send(match load.await {
+                Ok((url, image)) => CommandOutput::Decoded {
+                    generation,
+                    url,
+                    image,
+                },
+                Err((source, error)) => CommandOutput::Failed {
+                    generation,
+                    source,
+                    error,
+                },
+            }));
+        }));
+    }
+
+    /// Decodes and scales an image on a blocking thread, keeping the GTK thread responsive
+    pub(crate) async fn decode(
+        data: Data,
+        width: Option<i32>,
+        height: Option<i32>,
+    ) -> Result<Decoded, String> {
+        relm4::spawn_blocking(move || -> Result<Decoded, String> {
+            let pixbuf = data.into_pixbuf().map_err(|e| e.to_string())?;
+            Ok(Decoded::new(&resize_pixbuf(pixbuf, width, height)))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    pub(crate) async fn load_image(url: &str) -> Option<Data> {
+        let (bytes, fetched): (VecDeque<u8>, _) = match url {
+            url if url.starts_with("http") => match cache::Disk::default().get(url) {
+                Some(data) => (data.into(), None),
+                None => {
+                    let data = reqwest::get(url)
+                        .await
+                        .ok()?
+                        .error_for_status()
+                        .ok()?
+                        .bytes()
+                        .await
+                        .ok()?;
+                    (data.into_iter().collect(), Some(url.to_string()))
+                }
+            },
+            base64 if base64.starts_with("data:image") => (
+                base64
+                    .split("base64,")
+                    .collect::<Vec<&str>>()
+                    .get(1)
+                    .and_then(|img| base64::engine::general_purpose::STANDARD.decode(img).ok())?
+                    .into(),
+                None,
+            ),
+            file => {
+                let mut buffer = vec![];
+                File::open(file.strip_prefix("file://").unwrap_or(file))
+                    .ok()?
+                    .read_to_end(&mut buffer)
+                    .ok()?;
+                (buffer.into(), None)
+            }
+        };
+
+        Some(Data { bytes, fetched })
+    }
+
+    fn truncate<'a>(url: &'a str) -> &'a str {
+        url.char_indices()
+            .nth(50)
+            .map(|(size, _)| &url[..size])
+            .unwrap_or(&url)
+    }
+
+    fn cache() -> Rc<RefCell<cache::Memory>> {
+        thread_local! {
+            static CACHE: OnceLock<Rc<RefCell<cache::Memory>>> = OnceLock::new();
+        }
+
+        CACHE.with(|cache| {
+            cache
+                .get_or_init(move || {
+                    Rc::new(RefCell::new(cache::Memory::new(cache::Memory::CAPACITY)))
+                })
+                .clone()
+        })
+    }
+}
+
+/// Scales to the given size, a missing dimension keeps the aspect ratio
+fn resize_pixbuf(
+    pixbuf: gdk_pixbuf::Pixbuf,
+    width: Option<i32>,
+    height: Option<i32>,
+) -> gdk_pixbuf::Pixbuf {
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, pixbuf.height() * width / pixbuf.width().max(1)),
+        (None, Some(height)) => (pixbuf.width() * height / pixbuf.height().max(1), height),
+        (None, None) => return pixbuf,
+    };
+
+    if width <= 0 || height <= 0 {
+        return pixbuf;
+    }
+
+    pixbuf
+        .scale_simple(width, height, gdk_pixbuf::InterpType::Hyper)
+        .unwrap_or(pixbuf)
+}
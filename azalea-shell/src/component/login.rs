@@ -115,6 +115,14 @@ impl SimpleComponent for Model {
     }
 
     fn update(&mut self, input: Self::Input, _sender: ComponentSender<Self>) {
-        service::dbus::login::Service::send(input);
+        let action = match input {
+            service::dbus::login::Input::PowerOff => "power off",
+            service::dbus::login::Input::Reboot => "reboot",
+            service::dbus::login::Input::Suspend => "suspend",
+            service::dbus::login::Input::Hibernate => "hibernate",
+        };
+        if let Err(e) = service::dbus::login::Service::send(input) {
+            azalea_log::warning!("Failed to {action}: {e}");
+        }
     }
 }
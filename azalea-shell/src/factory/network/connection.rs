@@ -108,21 +108,27 @@ impl FactoryComponent for Model {
                 }
             }
             Input::Connect => {
-                service::dbus::network_manager::Service::send(
+                let sent = service::dbus::network_manager::Service::send(
                     service::dbus::network_manager::Input::ActivateConnection {
                         connection: Some(self.settings.clone()),
                         device: None,
                         specific_object: None,
                     },
                 );
+                if let Err(e) = sent {
+                    azalea_log::warning!("Failed to activate connection {}: {e}", self.name);
+                }
             }
             Input::Disconnect => {
                 if let Some(active_connection) = &self.active_connection {
-                    service::dbus::network_manager::Service::send(
+                    let sent = service::dbus::network_manager::Service::send(
                         service::dbus::network_manager::Input::DeactivateConnection {
                             active_connection: active_connection.clone(),
                         },
                     );
+                    if let Err(e) = sent {
+                        azalea_log::warning!("Failed to deactivate connection {}: {e}", self.name);
+                    }
                 }
             }
         }
@@ -136,13 +136,16 @@ impl FactoryComponent for Model {
                 }
             }
             Input::Connect => {
-                service::dbus::network_manager::Service::send(
+                let sent = service::dbus::network_manager::Service::send(
                     service::dbus::network_manager::Input::ActivateConnection {
                         connection: None,
                         device: Some(self.device.clone()),
                         specific_object: None,
                     },
                 );
+                if let Err(e) = sent {
+                    azalea_log::warning!("Failed to connect device {}: {e}", self.name);
+                }
             }
             Input::Disconnect => {
                 if let Some(proxy) = &self.proxy {
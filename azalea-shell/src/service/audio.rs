@@ -28,6 +28,9 @@ impl Default for Init {
 pub enum Input {
     /// Default sink volume
     SystemVolume(f64),
+
+    /// Relative change to default sink volume
+    AdjustSystemVolume(f64),
 }
 
 #[derive(Clone, Debug)]
@@ -41,16 +44,28 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = ();
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
     // TODO: Fix audio polling
     const DISABLE_EVENTS: bool = true;
 
-    async fn new(
+    fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
+        // Only the latest value matters while a slider is dragged, relative changes add up
+        azalea_service::Handler::new(init, 1, 1)
+            .replay(|_| Some(format!("volume")))
+            .coalesce(|input| match input {
+                Input::SystemVolume(_) => Some(format!("volume")),
+                Input::AdjustSystemVolume(_) => None,
+            })
+    }
+
+    async fn try_new(
         init: Self::Init,
         _: flume::Sender<Self::Input>,
         output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        let mixer = alsa::mixer::Mixer::new("default", false).unwrap();
+    ) -> azalea_service::Result<Self> {
+        let mixer = alsa::mixer::Mixer::new("default", false)?;
 
         let mut this = Self {
             mixer,
@@ -63,7 +78,7 @@ impl azalea_service::Service for Service {
             drop(output_sender.send(Output::SystemVolume(volume)));
         }
 
-        this
+        Ok(this)
     }
 
     async fn message(
@@ -73,18 +88,14 @@ impl azalea_service::Service for Service {
     ) {
         match input {
             Input::SystemVolume(volume_percent) => {
-                if let Some(selem) = self.mixer.find_selem(&self.selem_id) {
-                    let (min_volume, max_volume) = selem.get_playback_volume_range();
-
-                    let _ = selem.set_playback_volume_all(
-                        (volume_percent * (max_volume - min_volume) as f64) as i64 + min_volume,
-                    );
-
-                    drop(output_sender.send(Output::SystemVolume(volume_percent)));
-                } else {
-                    azalea_log::warning!("[AUDIO]: Failed to find Master selem");
-                }
+                self.set_system_volume(volume_percent, output_sender)
             }
+            Input::AdjustSystemVolume(delta) => match self.read_system_volume() {
+                Some((_, volume_percent)) => {
+                    self.set_system_volume((volume_percent + delta).clamp(0., 1.), output_sender)
+                }
+                None => azalea_log::warning!("[AUDIO]: Failed to read system volume"),
+            },
         }
     }
 
@@ -106,28 +117,58 @@ impl azalea_service::Service for Service {
 }
 
 impl Service {
-    fn get_system_volume(&mut self) -> Option<f64> {
+    fn set_system_volume(
+        &mut self,
+        volume_percent: f64,
+        output_sender: &broadcast::Sender<Output>,
+    ) {
         if let Some(selem) = self.mixer.find_selem(&self.selem_id) {
-            let mut count = 0;
-            let mut acc_volume = 0;
+            let (min_volume, max_volume) = selem.get_playback_volume_range();
 
-            for channel_id in SelemChannelId::all() {
-                if let Ok(channel_volume) = selem.get_playback_volume(*channel_id) {
-                    acc_volume += channel_volume;
-                    count += 1;
-                }
-            }
+            let _ = selem.set_playback_volume_all(
+                (volume_percent * (max_volume - min_volume) as f64) as i64 + min_volume,
+            );
 
-            let volume_int = acc_volume / count;
-            let (min_volume, max_volume) = selem.get_playback_volume_range();
+            drop(output_sender.send(Output::SystemVolume(volume_percent)));
+        } else {
+            azalea_log::warning!("[AUDIO]: Failed to find Master selem");
+        }
+    }
+
+    /// Average volume of all channels, both raw and as a percentage
+    fn read_system_volume(&self) -> Option<(i64, f64)> {
+        let selem = self.mixer.find_selem(&self.selem_id)?;
+        let mut count = 0;
+        let mut acc_volume = 0;
 
-            if self.previous_volume != volume_int {
-                let volume_percent = volume_int as f64 / ((max_volume - min_volume) as f64);
-                self.previous_volume = volume_int;
-                return Some(volume_percent);
+        for channel_id in SelemChannelId::all() {
+            if let Ok(channel_volume) = selem.get_playback_volume(*channel_id) {
+                acc_volume += channel_volume;
+                count += 1;
             }
         }
 
+        if count == 0 {
+            return None;
+        }
+
+        let volume_int = acc_volume / count;
+        let (min_volume, max_volume) = selem.get_playback_volume_range();
+
+        Some((
+            volume_int,
+            volume_int as f64 / ((max_volume - min_volume) as f64),
+        ))
+    }
+
+    fn get_system_volume(&mut self) -> Option<f64> {
+        let (volume_int, volume_percent) = self.read_system_volume()?;
+
+        if self.previous_volume != volume_int {
+            self.previous_volume = volume_int;
+            return Some(volume_percent);
+        }
+
         None
     }
 }
@@ -27,14 +27,23 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = ();
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
     const DISABLE_EVENTS: bool = true;
 
-    async fn new(
+    fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
+        // Only the latest value matters while a slider is dragged
+        azalea_service::Handler::new(init, 1, 1)
+            .replay(|_| Some(format!("brightness")))
+            .coalesce(|_| Some(format!("brightness")))
+    }
+
+    async fn try_new(
         _init: Self::Init,
         _: flume::Sender<Self::Input>,
         output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
+    ) -> azalea_service::Result<Self> {
         let mut devices: Vec<BrightnessDevice> = brightness::brightness_devices()
             .filter_map(|dev| dev.ok())
             .collect()
@@ -55,7 +64,7 @@ impl azalea_service::Service for Service {
             }
         });
 
-        Self { tx }
+        Ok(Self { tx })
     }
 
     async fn message(
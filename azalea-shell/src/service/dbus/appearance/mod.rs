@@ -0,0 +1,197 @@
+pub mod proxy;
+
+use futures_lite::stream::StreamExt;
+use proxy::{SettingChangedStream, SettingsProxy};
+use tokio::sync::broadcast;
+use zbus::zvariant::Value;
+
+pub use crate::service::theme::Variant;
+
+pub const NAMESPACE: &str = "org.freedesktop.appearance";
+pub const KEY: &str = "color-scheme";
+
+/// Appearance Service
+///
+/// Follows the color scheme preferred by the system, unless overridden
+#[derive(azalea_derive::StaticHandler)]
+pub struct Service {
+    proxy: SettingsProxy<'static>,
+    stream: Option<SettingChangedStream>,
+    color_scheme: ColorScheme,
+    preferred: Option<Variant>,
+}
+
+#[derive(Default, Clone)]
+pub struct Init {
+    pub dbus_connection: Option<zbus::Connection>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Input {
+    Update,
+    /// Switches to the opposite variant, overriding the system preference
+    Toggle,
+    /// Overrides the system preference, `None` follows it again
+    Set(Option<Variant>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Output {
+    Changed {
+        color_scheme: ColorScheme,
+        variant: Variant,
+    },
+}
+
+/// See: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorScheme {
+    #[default]
+    NoPreference,
+    PreferDark,
+    PreferLight,
+}
+
+impl ColorScheme {
+    pub fn variant(&self) -> Option<Variant> {
+        match self {
+            ColorScheme::NoPreference => None,
+            ColorScheme::PreferDark => Some(Variant::Dark),
+            ColorScheme::PreferLight => Some(Variant::Light),
+        }
+    }
+}
+
+impl From<&Value<'_>> for ColorScheme {
+    fn from(value: &Value<'_>) -> Self {
+        match value {
+            Value::Value(value) => ColorScheme::from(&**value),
+            Value::U32(1) => ColorScheme::PreferDark,
+            Value::U32(2) => ColorScheme::PreferLight,
+            _ => ColorScheme::NoPreference,
+        }
+    }
+}
+
+impl azalea_service::Service for Service {
+    type Init = Init;
+    type Input = Input;
+    type Event = ColorScheme;
+    type Output = Output;
+    type Query = ();
+    type Reply = ();
+
+    fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
+        azalea_service::Handler::new(init, 1, 1).replay(|_| Some(format!("color-scheme")))
+    }
+
+    async fn try_new(
+        init: Self::Init,
+        input_sender: flume::Sender<Self::Input>,
+        _: broadcast::Sender<Self::Output>,
+    ) -> azalea_service::Result<Self> {
+        let connection = match init.dbus_connection {
+            Some(connection) => connection,
+            None => zbus::Connection::session().await?,
+        };
+        let proxy = SettingsProxy::new(&connection).await?;
+
+        let stream = match proxy.receive_setting_changed().await {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                azalea_log::warning!("Failed to watch the settings portal: {e}");
+                None
+            }
+        };
+
+        drop(input_sender.send(Input::Update));
+
+        Ok(Self {
+            proxy,
+            stream,
+            color_scheme: ColorScheme::default(),
+            preferred: None,
+        })
+    }
+
+    async fn message(
+        &mut self,
+        input: Self::Input,
+        output_sender: &broadcast::Sender<Self::Output>,
+    ) {
+        match input {
+            Input::Update => self.color_scheme = self.read_color_scheme().await,
+            Input::Toggle => {
+                self.preferred = Some(match self.variant() {
+                    Variant::Light => Variant::Dark,
+                    Variant::Dark => Variant::Light,
+                })
+            }
+            Input::Set(variant) => self.preferred = variant,
+        }
+
+        self.send_output(output_sender);
+    }
+
+    async fn event_generator(&mut self) -> Self::Event {
+        let Some(stream) = &mut self.stream else {
+            return std::future::pending().await;
+        };
+
+        loop {
+            let Some(signal) = stream.next().await else {
+                azalea_log::warning!("Settings portal stream closed");
+                self.stream = None;
+                return std::future::pending().await;
+            };
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            if args.namespace == NAMESPACE && args.key == KEY {
+                return ColorScheme::from(&args.value);
+            }
+        }
+    }
+
+    async fn event_handler(
+        &mut self,
+        event: Self::Event,
+        output_sender: &broadcast::Sender<Self::Output>,
+    ) -> azalea_service::Result<()> {
+        azalea_log::debug!(Self, "Color scheme changed: {:?}", event);
+        self.color_scheme = event;
+        self.send_output(output_sender);
+        Ok(())
+    }
+}
+
+impl Service {
+    async fn read_color_scheme(&self) -> ColorScheme {
+        let value = match self.proxy.read_one(NAMESPACE, KEY).await {
+            Ok(value) => value,
+            // Older portals only implement `Read`
+            Err(_) => match self.proxy.read(NAMESPACE, KEY).await {
+                Ok(value) => value,
+                Err(e) => {
+                    azalea_log::debug!(Self, "Failed to read color scheme: {}", e);
+                    return ColorScheme::NoPreference;
+                }
+            },
+        };
+
+        ColorScheme::from(&*value)
+    }
+
+    fn variant(&self) -> Variant {
+        self.preferred
+            .or(self.color_scheme.variant())
+            .unwrap_or_default()
+    }
+
+    fn send_output(&self, output_sender: &broadcast::Sender<Output>) {
+        drop(output_sender.send(Output::Changed {
+            color_scheme: self.color_scheme,
+            variant: self.variant(),
+        }));
+    }
+}
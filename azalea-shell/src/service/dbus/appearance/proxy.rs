@@ -0,0 +1,22 @@
+use zbus::proxy;
+use zbus::zvariant::{OwnedValue, Value};
+
+/// Settings portal
+///
+/// DBus interface exposing desktop settings, like the preferred color scheme
+///
+/// See: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html
+#[proxy(
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop",
+    interface = "org.freedesktop.portal.Settings"
+)]
+pub trait Settings {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+    /// Deprecated in favor of `read_one`, the value is wrapped in an extra variant
+    fn read(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+    #[zbus(signal)]
+    fn setting_changed(&self, namespace: &str, key: &str, value: Value<'_>) -> zbus::Result<()>;
+}
@@ -28,11 +28,21 @@ pub type DeviceAdress = String;
 #[derive(Clone, Debug)]
 pub enum Input {
     Power(bool),
-    Adapters(flume::Sender<Vec<AdapterName>>),
-    Devices(flume::Sender<HashMap<String, Device>>),
     Connect(DeviceAdress, bool),
 }
 
+#[derive(Debug)]
+pub enum Query {
+    Adapters,
+    Devices,
+}
+
+#[derive(Debug)]
+pub enum Reply {
+    Adapters(Vec<AdapterName>),
+    Devices(HashMap<String, Device>),
+}
+
 pub enum Event {}
 
 #[derive(Clone, Debug)]
@@ -46,15 +56,17 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = ();
     type Output = Output;
+    type Query = Query;
+    type Reply = Reply;
     const DISABLE_EVENTS: bool = true;
 
-    async fn new(
+    async fn try_new(
         _init: Self::Init,
         _input: flume::Sender<Self::Input>,
         output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        let session = bluer::Session::new().await.unwrap();
-        let adapter = session.default_adapter().await.unwrap();
+    ) -> azalea_service::Result<Self> {
+        let session = bluer::Session::new().await?;
+        let adapter = session.default_adapter().await?;
         let devices =
             futures_lite::stream::iter(adapter.device_addresses().await.unwrap_or_default())
                 .then(|addr| {
@@ -70,10 +82,35 @@ impl azalea_service::Service for Service {
                 .collect::<HashMap<String, bluer::Device>>()
                 .await;
 
-        Self {
+        Ok(Self {
             session,
             adapter,
             devices,
+        })
+    }
+
+    async fn query(&mut self, query: Self::Query) -> Self::Reply {
+        match query {
+            Query::Adapters => {
+                Reply::Adapters(self.session.adapter_names().await.unwrap_or_default())
+            }
+            Query::Devices => Reply::Devices(
+                futures_lite::stream::iter(self.devices.iter())
+                    .then(|(address, device)| async move {
+                        let address = address.to_string();
+                        (
+                            address.clone(),
+                            Device {
+                                address,
+                                name: device.name().await.unwrap_or(None),
+                                is_connected: device.is_connected().await.unwrap_or(false),
+                                icon: device.icon().await.unwrap_or(None),
+                            },
+                        )
+                    })
+                    .collect::<HashMap<String, Device>>()
+                    .await,
+            ),
         }
     }
 
@@ -94,29 +131,6 @@ impl azalea_service::Service for Service {
                     drop(output_sender.send(Output::Powered(on)));
                 }
             }
-            Input::Adapters(sender) => {
-                let names = self.session.adapter_names().await.unwrap_or_default();
-                drop(sender.send(names));
-            }
-            Input::Devices(sender) => drop(
-                sender.send(
-                    futures_lite::stream::iter(self.devices.iter())
-                        .then(|(address, device)| async move {
-                            let address = address.to_string();
-                            (
-                                address.clone(),
-                                Device {
-                                    address,
-                                    name: device.name().await.unwrap_or(None),
-                                    is_connected: device.is_connected().await.unwrap_or(false),
-                                    icon: device.icon().await.unwrap_or(None),
-                                },
-                            )
-                        })
-                        .collect::<HashMap<String, Device>>()
-                        .await,
-                ),
-            ),
             Input::Connect(device_address, connect) => match self.devices.get(&device_address) {
                 Some(device) => {
                     if connect {
@@ -15,9 +15,16 @@ pub struct Service {
 }
 
 #[derive(Debug)]
-pub enum Input {
-    QueryObjects(tokio::sync::oneshot::Sender<Vec<OwnedBusName>>),
-    QueryServiceExists(String, tokio::sync::oneshot::Sender<bool>),
+pub enum Query {
+    Objects,
+    /// Whether any object name contains the given one
+    ServiceExists(String),
+}
+
+#[derive(Debug)]
+pub enum Reply {
+    Objects(Vec<OwnedBusName>),
+    ServiceExists(bool),
 }
 
 #[derive(Clone, Debug)]
@@ -28,53 +35,42 @@ pub enum Output {
 
 impl azalea_service::Service for Service {
     type Init = Option<zbus::Connection>;
-    type Input = Input;
+    type Input = ();
     type Event = Output;
     type Output = Output;
+    type Query = Query;
+    type Reply = Reply;
 
     fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
         azalea_service::Handler::new(init, 4, 8)
     }
 
-    async fn new(
+    async fn try_new(
         connection: Self::Init,
         _input_sender: flume::Sender<Self::Input>,
         _output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        let connection = connection.unwrap_or(zbus::Connection::session().await.unwrap());
-        let proxy = DBusProxy::new(&connection).await.unwrap();
+    ) -> azalea_service::Result<Self> {
+        let connection = match connection {
+            Some(connection) => connection,
+            None => zbus::Connection::session().await?,
+        };
+        let proxy = DBusProxy::new(&connection).await?;
         let mut objects: HashSet<OwnedBusName> = Default::default();
 
         for name in proxy.list_names().await.unwrap_or_default() {
             objects.insert(name);
         }
 
-        let stream = proxy.receive_name_owner_changed().await.unwrap();
+        let stream = proxy.receive_name_owner_changed().await?;
 
-        Self { stream, objects }
+        Ok(Self { stream, objects })
     }
 
-    async fn message(
-        &mut self,
-        input: Self::Input,
-        _output_sender: &broadcast::Sender<Self::Output>,
-    ) {
-        match input {
-            Input::QueryObjects(sender) => {
-                let names = self.objects.clone().into_iter().collect();
-                drop(sender.send(names));
-            }
-            Input::QueryServiceExists(service, sender) => {
-                let mut found = false;
-
-                for name in &self.objects {
-                    if name.contains(&service) {
-                        found = true;
-                        break;
-                    }
-                }
-
-                let _ = sender.send(found);
+    async fn query(&mut self, query: Self::Query) -> Self::Reply {
+        match query {
+            Query::Objects => Reply::Objects(self.objects.iter().cloned().collect()),
+            Query::ServiceExists(service) => {
+                Reply::ServiceExists(self.objects.iter().any(|name| name.contains(&service)))
             }
         }
     }
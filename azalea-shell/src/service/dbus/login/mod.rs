@@ -32,21 +32,24 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = Event;
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
     const DISABLE_EVENTS: bool = true;
 
-    async fn new(
+    async fn try_new(
         init: Self::Init,
         _input: flume::Sender<Self::Input>,
         _: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        let connection = init
-            .dbus_connection
-            .unwrap_or(zbus::Connection::system().await.unwrap());
+    ) -> azalea_service::Result<Self> {
+        let connection = match init.dbus_connection {
+            Some(connection) => connection,
+            None => zbus::Connection::system().await?,
+        };
 
-        let proxy = LoginManagerProxy::new(&connection).await.unwrap();
+        let proxy = LoginManagerProxy::new(&connection).await?;
 
-        Self { proxy }
+        Ok(Self { proxy })
     }
 
     async fn message(
@@ -1,5 +1,6 @@
 //! # Azalea services that rely on DBus
 
+pub mod appearance;
 pub mod bluez;
 pub mod discovery;
 pub mod login;
@@ -2,9 +2,11 @@ use std::collections::HashMap;
 
 use azalea_service::{ListenerHandle, StaticHandler};
 use futures_lite::stream::StreamExt;
-use tokio::sync::{broadcast, oneshot};
+use tokio::sync::broadcast;
 pub use zbus_names::OwnedBusName;
 
+use super::discovery;
+
 pub mod proxy;
 use proxy::{Metadata, PlaybackRate, PlaybackStatus, PlayerProxy};
 
@@ -56,17 +58,19 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = ();
     type Output = Output;
+    type Query = ();
+    type Reply = ();
     const DISABLE_EVENTS: bool = true;
 
     fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
         azalea_service::Handler::new(init, 8, 8)
     }
 
-    async fn new(
+    async fn try_new(
         init: Self::Init,
         input_sender: flume::Sender<Self::Input>,
         output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
+    ) -> azalea_service::Result<Self> {
         let connection = init
             .dbus_connection
             .unwrap_or(zbus::Connection::session().await.unwrap());
@@ -97,10 +101,8 @@ impl azalea_service::Service for Service {
             players: Default::default(),
         };
 
-        let (tx, rx) = oneshot::channel();
-        super::discovery::Service::send(super::discovery::Input::QueryObjects(tx));
-        match rx.await {
-            Ok(names) => {
+        match discovery::Service::request(discovery::Query::Objects).await {
+            Ok(discovery::Reply::Objects(names)) => {
                 for name in names {
                     if !name.contains("org.mpris.MediaPlayer2") {
                         continue;
@@ -110,10 +112,11 @@ impl azalea_service::Service for Service {
                         .await;
                 }
             }
-            Err(e) => azalea_log::debug!("Failed to send: {e}"),
+            Ok(reply) => azalea_log::warning!("Unexpected discovery reply: {reply:?}"),
+            Err(e) => azalea_log::warning!("Failed to query dbus objects: {e}"),
         }
 
-        service
+        Ok(service)
     }
 
     async fn message(
@@ -175,28 +178,19 @@ impl azalea_service::Service for Service {
             Input::Action(action) => {
                 azalea_log::debug!(Self, "Triggered action: {:?}", action);
                 // TODO: return anyhow error
+                let bus_name = match &action {
+                    Action::PlayPause(bus_name)
+                    | Action::Previous(bus_name)
+                    | Action::Next(bus_name) => bus_name,
+                };
+                let Some(player) = self.players.get(bus_name) else {
+                    azalea_log::warning!(Self, "Player not found: {}", bus_name);
+                    return;
+                };
                 match action {
-                    Action::PlayPause(bus_name) => {
-                        drop(
-                            self.players
-                                .get(&bus_name)
-                                .map(|p| p.play_pause())
-                                .unwrap()
-                                .await,
-                        );
-                    }
-                    Action::Previous(bus_name) => {
-                        drop(
-                            self.players
-                                .get(&bus_name)
-                                .map(|p| p.previous())
-                                .unwrap()
-                                .await,
-                        );
-                    }
-                    Action::Next(bus_name) => {
-                        drop(self.players.get(&bus_name).map(|p| p.next()).unwrap().await);
-                    }
+                    Action::PlayPause(_) => drop(player.play_pause().await),
+                    Action::Previous(_) => drop(player.previous().await),
+                    Action::Next(_) => drop(player.next().await),
                 }
             }
         }
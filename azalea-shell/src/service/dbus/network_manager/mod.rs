@@ -68,21 +68,33 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = Event;
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
     fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
-        azalea_service::Handler::new(init, 4, 8)
+        azalea_service::Handler::new(init, 4, 8).replay(|output| {
+            let key = match output {
+                Output::Connections(_) => "connections",
+                Output::Devices(_) => "devices",
+                Output::NetworkingEnabledChanged(_) => "networking-enabled",
+                Output::StateChanged(_) => "state",
+                Output::ConnectivityChanged(_) => "connectivity",
+            };
+            Some(format!("{key}"))
+        })
     }
 
-    async fn new(
+    async fn try_new(
         init: Self::Init,
         _input: flume::Sender<Self::Input>,
         _: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        let connection = init
-            .dbus_connection
-            .unwrap_or(zbus::Connection::system().await.unwrap());
-        let proxy = NetworkManagerProxy::new(&connection).await.unwrap();
-        let settings_proxy = NetworkManagerSettingsProxy::new(&connection).await.unwrap();
+    ) -> azalea_service::Result<Self> {
+        let connection = match init.dbus_connection {
+            Some(connection) => connection,
+            None => zbus::Connection::system().await?,
+        };
+        let proxy = NetworkManagerProxy::new(&connection).await?;
+        let settings_proxy = NetworkManagerSettingsProxy::new(&connection).await?;
 
         azalea_log::debug!(
             Self,
@@ -90,7 +102,7 @@ impl azalea_service::Service for Service {
             proxy.version().await.unwrap_or_default()
         );
 
-        Self {
+        Ok(Self {
             streams: Streams {
                 enable: proxy.receive_networking_enabled_changed().await,
                 state: proxy.receive_state_changed().await,
@@ -98,7 +110,7 @@ impl azalea_service::Service for Service {
             },
             settings_proxy,
             proxy,
-        }
+        })
     }
 
     async fn message(
@@ -2,14 +2,16 @@ pub mod service;
 
 use std::collections::HashMap;
 
-use azalea_service::StaticHandler;
-use tokio::sync::{broadcast, oneshot};
+use azalea_service::{ListenerHandle, StaticHandler};
+use tokio::sync::broadcast;
 
 #[derive(azalea_derive::StaticHandler)]
 pub struct Service {
     notifications: HashMap<u32, service::Notification>,
     conn: Option<zbus::Connection>,
     rx: flume::Receiver<service::Event>,
+    // Keeps discovery running, so it can be queried
+    _discovery: ListenerHandle,
 }
 
 pub struct Streams {}
@@ -36,27 +38,28 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = Event;
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
     fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
         azalea_service::Handler::new(init, 16, 16)
     }
 
-    async fn new(
+    async fn try_new(
         _init: Self::Init,
         _input: flume::Sender<Self::Input>,
         _output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        let (tx, rx) = flume::unbounded();
-        let (discovery_tx, discovery_rx) = oneshot::channel();
+    ) -> azalea_service::Result<Self> {
+        use super::discovery;
 
-        super::discovery::Service::send(super::discovery::Input::QueryServiceExists(
-            format!("org.freedesktop.StatusNotifierWatcher"),
-            discovery_tx,
-        ));
+        let (tx, rx) = flume::unbounded();
+        let discovery_handle = discovery::Service::listen(|_| true);
+        let query =
+            discovery::Query::ServiceExists(format!("org.freedesktop.StatusNotifierWatcher"));
 
-        let conn = match discovery_rx.await {
-            Ok(true) => None,
-            Ok(false) => {
+        let conn = match discovery::Service::request(query).await {
+            Ok(discovery::Reply::ServiceExists(true)) => None,
+            Ok(discovery::Reply::ServiceExists(false)) => {
                 let notifications = service::Notifications::new(tx);
 
                 if let Ok(conn) = zbus::conn::Builder::session()
@@ -70,17 +73,22 @@ impl azalea_service::Service for Service {
                     None
                 }
             }
+            Ok(reply) => {
+                azalea_log::warning!("Unexpected discovery reply: {reply:?}");
+                None
+            }
             Err(e) => {
                 azalea_log::warning!("Failed to query for other notification servers: {e}");
                 None
             }
         };
 
-        Self {
+        Ok(Self {
             notifications: Default::default(),
             conn,
             rx,
-        }
+            _discovery: discovery_handle,
+        })
     }
 
     async fn message(
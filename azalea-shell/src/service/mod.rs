@@ -4,5 +4,6 @@ pub mod audio;
 pub mod brightness;
 pub mod dbus;
 pub mod search;
+pub mod theme;
 pub mod time;
 pub mod weather;
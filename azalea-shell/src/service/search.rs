@@ -52,9 +52,12 @@ pub enum Input {
 
     /// Search only for applications
     SearchApplication(String),
+}
 
+#[derive(Debug)]
+pub enum Query {
     /// Get all applications in case you want to search "locally"
-    GetAllApplications(flume::Sender<Vec<AppInfo>>),
+    AllApplications,
 }
 
 #[derive(Clone, Debug)]
@@ -67,14 +70,16 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = ();
     type Output = Output;
+    type Query = Query;
+    type Reply = Vec<AppInfo>;
     const DISABLE_EVENTS: bool = true;
 
-    async fn new(
+    async fn try_new(
         _init: Self::Init,
         _: flume::Sender<Self::Input>,
         _: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        Self {
+    ) -> azalea_service::Result<Self> {
+        Ok(Self {
             applications: gio::AppInfo::all()
                 .into_iter()
                 .map(|app| {
@@ -82,6 +87,12 @@ impl azalea_service::Service for Service {
                     (app.id.clone(), app)
                 })
                 .collect(),
+        })
+    }
+
+    async fn query(&mut self, query: Self::Query) -> Self::Reply {
+        match query {
+            Query::AllApplications => self.applications.values().cloned().collect(),
         }
     }
 
@@ -102,16 +113,6 @@ impl azalea_service::Service for Service {
                     )),
                 );
             }
-            Input::GetAllApplications(sender) => {
-                drop(
-                    sender.send(
-                        self.applications
-                            .values()
-                            .map(|app| app.to_owned())
-                            .collect(),
-                    ),
-                );
-            }
             Input::LaunchApplication(app_id) => {
                 let Some(app) = self.applications.get(&app_id) else {
                     azalea_log::warning!("Application not found: {app_id}");
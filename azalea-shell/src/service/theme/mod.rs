@@ -0,0 +1,134 @@
+//! # Theme service
+//!
+//! Generates a color palette from the wallpaper, exposed to styles as scss variables
+
+pub mod palette;
+pub use palette::{Palette, Variant};
+
+use relm4::gtk::gdk_pixbuf;
+use tokio::sync::broadcast;
+
+use crate::component::image;
+
+#[derive(azalea_derive::StaticHandler)]
+pub struct Service {
+    wallpaper: Option<String>,
+    palette: Palette,
+    variant: Variant,
+}
+
+#[derive(Clone, Default)]
+pub struct Init {}
+
+#[derive(Clone, Debug)]
+pub enum Input {
+    /// Wallpaper image (path, url or base64), the palette is only regenerated if it changed
+    Wallpaper(String),
+    Variant(Variant),
+}
+
+#[derive(Clone, Debug)]
+pub enum Output {
+    Palette { palette: Palette, variant: Variant },
+}
+
+impl azalea_service::Service for Service {
+    type Init = Init;
+    type Input = Input;
+    type Event = ();
+    type Output = Output;
+    type Query = ();
+    type Reply = ();
+
+    const DISABLE_EVENTS: bool = true;
+
+    fn handler(init: Self::Init) -> azalea_service::Handler<Self> {
+        azalea_service::Handler::new(init, 1, 1).replay(|_| Some(format!("palette")))
+    }
+
+    async fn try_new(
+        _init: Self::Init,
+        _: flume::Sender<Self::Input>,
+        _output_sender: broadcast::Sender<Self::Output>,
+    ) -> azalea_service::Result<Self> {
+        Ok(Self {
+            wallpaper: None,
+            palette: Palette::default(),
+            variant: Variant::default(),
+        })
+    }
+
+    async fn message(
+        &mut self,
+        input: Self::Input,
+        output_sender: &broadcast::Sender<Self::Output>,
+    ) {
+        match input {
+            Input::Wallpaper(wallpaper) => {
+                if self.wallpaper.as_ref() == Some(&wallpaper) {
+                    return;
+                }
+
+                let Some(palette) = Self::generate_palette(&wallpaper).await else {
+                    azalea_log::warning!("Failed to generate palette from: {wallpaper}");
+                    return;
+                };
+
+                azalea_log::debug!(
+                    Self,
+                    "Generated palette with source color {}",
+                    palette.source.to_hex()
+                );
+                self.wallpaper = Some(wallpaper);
+                self.palette = palette;
+            }
+            Input::Variant(variant) => {
+                if self.variant == variant {
+                    return;
+                }
+                self.variant = variant;
+            }
+        }
+
+        drop(output_sender.send(Output::Palette {
+            palette: self.palette.clone(),
+            variant: self.variant,
+        }));
+    }
+}
+
+impl Service {
+    /// Images are scaled down before sampling, details don't matter for the dominant color
+    const SAMPLE_SIZE: i32 = 64;
+
+    async fn generate_palette(wallpaper: &str) -> Option<Palette> {
+        let data = image::Model::load_image(wallpaper).await?;
+
+        // Decoding a full size wallpaper takes a while, keep it off the service loop
+        relm4::spawn_blocking(move || {
+            let pixbuf = data.into_pixbuf().ok()?.scale_simple(
+                Self::SAMPLE_SIZE,
+                Self::SAMPLE_SIZE,
+                gdk_pixbuf::InterpType::Bilinear,
+            )?;
+
+            let bytes = pixbuf.read_pixel_bytes();
+            let channels = pixbuf.n_channels() as usize;
+            let rowstride = pixbuf.rowstride() as usize;
+            let width = pixbuf.width() as usize;
+
+            let pixels = (0..pixbuf.height() as usize).flat_map(|y| {
+                let row = &bytes[y * rowstride..];
+                (0..width).map(move |x| palette::Color {
+                    r: row[x * channels],
+                    g: row[x * channels + 1],
+                    b: row[x * channels + 2],
+                })
+            });
+
+            Some(Palette::from_pixels(pixels))
+        })
+        .await
+        .ok()?
+    }
+}
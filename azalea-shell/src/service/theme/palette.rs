@@ -0,0 +1,369 @@
+//! Palette generation, roles are named after the material color scheme
+
+#[derive(
+    clap::ValueEnum, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq,
+)]
+pub enum Variant {
+    Light,
+    #[default]
+    Dark,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Hue in degrees, saturation and lightness in 0..=1
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            self.r as f64 / 255.,
+            self.g as f64 / 255.,
+            self.b as f64 / 255.,
+        );
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.;
+        let delta = max - min;
+
+        if delta == 0. {
+            return (0., 0., lightness);
+        }
+
+        let saturation = delta / (1. - (2. * lightness - 1.).abs());
+        let hue = if max == r {
+            60. * ((g - b) / delta).rem_euclid(6.)
+        } else if max == g {
+            60. * ((b - r) / delta + 2.)
+        } else {
+            60. * ((r - g) / delta + 4.)
+        };
+
+        (hue, saturation, lightness)
+    }
+
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+        let hue = hue.rem_euclid(360.) / 60.;
+        let x = chroma * (1. - (hue % 2. - 1.).abs());
+        let (r, g, b) = match hue as u8 {
+            0 => (chroma, x, 0.),
+            1 => (x, chroma, 0.),
+            2 => (0., chroma, x),
+            3 => (0., x, chroma),
+            4 => (x, 0., chroma),
+            _ => (chroma, 0., x),
+        };
+        let m = lightness - chroma / 2.;
+        let channel = |value: f64| ((value + m) * 255.).round().clamp(0., 255.) as u8;
+
+        Self {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+        }
+    }
+}
+
+/// Hue and saturation of a group of colors, shades are picked by tone (lightness 0..=100)
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Tonal {
+    hue: f64,
+    saturation: f64,
+}
+
+impl Tonal {
+    fn tone(&self, tone: u8) -> Color {
+        Color::from_hsl(self.hue, self.saturation, tone as f64 / 100.)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub source: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            source: Color {
+                r: 0xf3,
+                g: 0x63,
+                b: 0x83,
+            },
+        }
+    }
+}
+
+impl Palette {
+    const HUE_BUCKETS: usize = 36;
+
+    /// Picks the most common hue, weighting each pixel by how colorful it is
+    pub fn from_pixels(pixels: impl Iterator<Item = Color>) -> Self {
+        let mut buckets = [(0., 0., 0., 0.); Self::HUE_BUCKETS];
+
+        for pixel in pixels {
+            let (hue, saturation, lightness) = pixel.to_hsl();
+            // Near black, white or gray pixels carry barely any hue
+            let weight = saturation * (1. - (2. * lightness - 1.).abs());
+            if weight < 0.05 {
+                continue;
+            }
+
+            let bucket =
+                &mut buckets[(hue / 360. * Self::HUE_BUCKETS as f64) as usize % Self::HUE_BUCKETS];
+            bucket.0 += weight;
+            bucket.1 += pixel.r as f64 * weight;
+            bucket.2 += pixel.g as f64 * weight;
+            bucket.3 += pixel.b as f64 * weight;
+        }
+
+        let Some((weight, r, g, b)) = buckets
+            .into_iter()
+            .filter(|(weight, ..)| *weight > 0.)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+        else {
+            return Self::default();
+        };
+
+        Self {
+            source: Color {
+                r: (r / weight) as u8,
+                g: (g / weight) as u8,
+                b: (b / weight) as u8,
+            },
+        }
+    }
+
+    /// Color roles for the given variant
+    pub fn roles(&self, variant: Variant) -> Vec<(&'static str, Color)> {
+        let (hue, saturation, _) = self.source.to_hsl();
+        let saturation = saturation.clamp(0.35, 0.9);
+
+        let primary = Tonal { hue, saturation };
+        let secondary = Tonal {
+            hue,
+            saturation: saturation * 0.35,
+        };
+        let tertiary = Tonal {
+            hue: hue + 60.,
+            saturation: saturation * 0.7,
+        };
+        let error = Tonal {
+            hue: 5.,
+            saturation: 0.9,
+        };
+        let neutral = Tonal {
+            hue,
+            saturation: 0.08,
+        };
+        let neutral_variant = Tonal {
+            hue,
+            saturation: 0.12,
+        };
+
+        let mut roles = vec![
+            ("source-color", self.source),
+            ("scrim", Color::BLACK),
+            ("shadow", Color::BLACK),
+        ];
+
+        let accents: [(Tonal, [&'static str; 8]); 3] = [
+            (
+                primary,
+                [
+                    "primary",
+                    "on-primary",
+                    "primary-container",
+                    "on-primary-container",
+                    "primary-fixed",
+                    "primary-fixed-dim",
+                    "on-primary-fixed",
+                    "on-primary-fixed-variant",
+                ],
+            ),
+            (
+                secondary,
+                [
+                    "secondary",
+                    "on-secondary",
+                    "secondary-container",
+                    "on-secondary-container",
+                    "secondary-fixed",
+                    "secondary-fixed-dim",
+                    "on-secondary-fixed",
+                    "on-secondary-fixed-variant",
+                ],
+            ),
+            (
+                tertiary,
+                [
+                    "tertiary",
+                    "on-tertiary",
+                    "tertiary-container",
+                    "on-tertiary-container",
+                    "tertiary-fixed",
+                    "tertiary-fixed-dim",
+                    "on-tertiary-fixed",
+                    "on-tertiary-fixed-variant",
+                ],
+            ),
+        ];
+        // Fixed roles share the same tones on both variants
+        let accent_tones = match variant {
+            Variant::Light => [40, 100, 90, 10, 90, 80, 10, 30],
+            Variant::Dark => [80, 20, 30, 90, 90, 80, 10, 30],
+        };
+        for (tonal, names) in accents {
+            roles.extend(
+                names
+                    .into_iter()
+                    .zip(accent_tones)
+                    .map(|(name, tone)| (name, tonal.tone(tone))),
+            );
+        }
+
+        let (inverse_primary, surface_tint) = match variant {
+            Variant::Light => (primary.tone(80), primary.tone(40)),
+            Variant::Dark => (primary.tone(40), primary.tone(80)),
+        };
+        roles.extend([
+            ("inverse-primary", inverse_primary),
+            ("surface-tint", surface_tint),
+        ]);
+
+        let tones: [(&'static str, Tonal, u8, u8); 21] = [
+            ("error", error, 40, 80),
+            ("on-error", error, 100, 20),
+            ("error-container", error, 90, 30),
+            ("on-error-container", error, 10, 90),
+            ("background", neutral, 98, 6),
+            ("on-background", neutral, 10, 90),
+            ("surface", neutral, 98, 6),
+            ("surface-dim", neutral, 87, 6),
+            ("surface-bright", neutral, 98, 24),
+            ("surface-container-lowest", neutral, 100, 4),
+            ("surface-container-low", neutral, 96, 10),
+            ("surface-container", neutral, 94, 12),
+            ("surface-container-high", neutral, 92, 17),
+            ("surface-container-highest", neutral, 90, 22),
+            ("on-surface", neutral, 10, 90),
+            ("surface-variant", neutral_variant, 90, 30),
+            ("on-surface-variant", neutral_variant, 30, 80),
+            ("outline", neutral_variant, 50, 60),
+            ("outline-variant", neutral_variant, 80, 30),
+            ("inverse-surface", neutral, 20, 90),
+            ("inverse-on-surface", neutral, 95, 20),
+        ];
+        roles.extend(tones.into_iter().map(|(name, tonal, light, dark)| {
+            (
+                name,
+                tonal.tone(match variant {
+                    Variant::Light => light,
+                    Variant::Dark => dark,
+                }),
+            )
+        }));
+
+        roles
+    }
+
+    /// Scss variables, roles of the active variant are declared without prefix and both variants
+    /// are declared with a `light-` or `dark-` prefix
+    pub fn variables(&self, variant: Variant) -> Vec<(String, String)> {
+        let prefixed = [(Variant::Light, "light-"), (Variant::Dark, "dark-")]
+            .into_iter()
+            .flat_map(|(variant, prefix)| {
+                self.roles(variant)
+                    .into_iter()
+                    .map(move |(name, color)| (format!("{prefix}{name}"), color.to_hex()))
+            });
+
+        self.roles(variant)
+            .into_iter()
+            .map(|(name, color)| (name.to_string(), color.to_hex()))
+            .chain(prefixed)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const BLUE: Color = Color { r: 0, g: 0, b: 255 };
+
+    #[test]
+    fn from_pixels_most_common_hue() {
+        let pixels = [RED; 10].into_iter().chain([BLUE; 3]);
+        assert_eq!(Palette::from_pixels(pixels).source, RED);
+    }
+
+    #[test]
+    fn from_pixels_ignores_grays() {
+        let gray = Color {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+        let white = Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let pixels = [gray; 100]
+            .into_iter()
+            .chain([Color::BLACK; 100])
+            .chain([white; 100])
+            .chain([BLUE]);
+        assert_eq!(Palette::from_pixels(pixels).source, BLUE);
+    }
+
+    #[test]
+    fn from_pixels_weights_by_colorfulness() {
+        let dull_red = Color {
+            r: 150,
+            g: 100,
+            b: 100,
+        };
+        let pixels = [dull_red; 5].into_iter().chain([BLUE; 2]);
+        assert_eq!(Palette::from_pixels(pixels).source, BLUE);
+    }
+
+    #[test]
+    fn from_pixels_averages_bucket() {
+        let orange_red = Color {
+            r: 255,
+            g: 20,
+            b: 0,
+        };
+        let pixels = [RED, orange_red].into_iter();
+        assert_eq!(
+            Palette::from_pixels(pixels).source,
+            Color {
+                r: 255,
+                g: 10,
+                b: 0
+            }
+        );
+    }
+
+    #[test]
+    fn from_pixels_default() {
+        assert_eq!(Palette::from_pixels(std::iter::empty()), Palette::default());
+        assert_eq!(
+            Palette::from_pixels([Color::BLACK; 10].into_iter()),
+            Palette::default()
+        );
+    }
+}
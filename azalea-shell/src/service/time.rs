@@ -33,16 +33,18 @@ impl azalea_service::Service for Service {
     type Input = String;
     type Event = ();
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
-    async fn new(
+    async fn try_new(
         init: Self::Init,
         _: flume::Sender<Self::Input>,
         _: broadcast::Sender<Self::Output>,
-    ) -> Self {
-        Self {
+    ) -> azalea_service::Result<Self> {
+        Ok(Self {
             interval_duration: init.interval_duration,
             ..Default::default()
-        }
+        })
     }
 
     async fn message(
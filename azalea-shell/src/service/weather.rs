@@ -23,19 +23,21 @@ impl azalea_service::Service for Service {
     type Input = Input;
     type Event = ();
     type Output = Output;
+    type Query = ();
+    type Reply = ();
 
     const DISABLE_EVENTS: bool = true;
 
-    async fn new(
+    async fn try_new(
         _init: Self::Init,
         sender: flume::Sender<Self::Input>,
         _output_sender: broadcast::Sender<Self::Output>,
-    ) -> Self {
+    ) -> azalea_service::Result<Self> {
         let client = open_meteo_rs::Client::new();
 
         drop(sender.send(Input::Update));
 
-        Self { client }
+        Ok(Self { client })
     }
 
     async fn message(
@@ -32,16 +32,17 @@ macro_rules! init {
         }
 
         Config {
-            $($config_name: ident: $config_type: ty,)*
+            $($(#[$config_meta: meta])* $config_name: ident: $config_type: ty,)*
         }
     ) => {
         pub struct Model {
             $($model_vis $model_name: $model_type,)*
         }
 
-        #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+        #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
         pub struct Config {
             $(
+                $(#[$config_meta])*
                 pub $config_name: $config_type,
             )*
         }
@@ -66,11 +67,33 @@ macro_rules! register_widgets {
         }
 
         #[allow(dead_code)]
-        #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+        #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+        #[serde(try_from = "ConfigOrTemplate")]
         pub enum ConfigWrapper {
             $($window(<$model as crate::window::ModelExt>::Config)),+
         }
 
+        /// What a config file may have where a `ConfigWrapper` is expected
+        #[derive(serde::Deserialize, schemars::JsonSchema)]
+        pub enum ConfigOrTemplate {
+            $($window(<$model as crate::window::ModelExt>::Config),)+
+            /// Refers to a widget template by name
+            Template(azalea_core::config::template::Name),
+        }
+
+        impl TryFrom<ConfigOrTemplate> for ConfigWrapper {
+            type Error = String;
+
+            fn try_from(config: ConfigOrTemplate) -> Result<Self, Self::Error> {
+                match config {
+                    $(ConfigOrTemplate::$window(config) => Ok(ConfigWrapper::$window(config)),)+
+                    ConfigOrTemplate::Template(name) => {
+                        azalea_core::config::template::resolve(&name)
+                    }
+                }
+            }
+        }
+
         #[allow(dead_code)]
         impl ConfigWrapper {
             pub fn build_widget(self) -> (WidgetWrapper, gtk::Widget) {
@@ -19,6 +19,17 @@ crate::init! {
     }
 }
 
+impl Config {
+    pub fn validate(&self) -> Vec<String> {
+        self.start
+            .iter()
+            .chain(&self.center)
+            .chain(&self.end)
+            .flat_map(|widget| widget.validate())
+            .collect()
+    }
+}
+
 #[component(pub)]
 impl SimpleComponent for Model {
     type Init = Init;
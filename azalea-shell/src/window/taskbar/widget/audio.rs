@@ -97,8 +97,8 @@ impl SimpleComponent for Model {
                 let volume = self.system_volume - delta * 0.01;
                 if volume >= 0. && volume <= 1. {
                     self.system_volume = volume;
-                    service::audio::Service::send(service::audio::Input::SystemVolume(
-                        self.system_volume,
+                    drop(service::audio::Service::send(
+                        service::audio::Input::SystemVolume(self.system_volume),
                     ));
                 }
             }
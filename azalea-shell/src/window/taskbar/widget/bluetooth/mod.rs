@@ -120,11 +120,20 @@ impl Component for Model {
             ),
         };
 
-        let (tx, rx) = flume::bounded(1);
-        service::dbus::bluez::Service::send(service::dbus::bluez::Input::Devices(tx));
         sender.oneshot_command(async move {
-            let devices = rx.recv_async().await.unwrap();
-            CommandOutput::SetDevices(devices)
+            use service::dbus::bluez::{Query, Reply, Service};
+
+            match Service::request(Query::Devices).await {
+                Ok(Reply::Devices(devices)) => CommandOutput::SetDevices(devices),
+                Ok(reply) => {
+                    azalea_log::warning!("Unexpected bluetooth reply: {reply:?}");
+                    CommandOutput::SetDevices(Default::default())
+                }
+                Err(e) => {
+                    azalea_log::warning!("Failed to get bluetooth devices: {e}");
+                    CommandOutput::SetDevices(Default::default())
+                }
+            }
         });
 
         let devices_widget = model.devices_menu.widget();
@@ -136,9 +145,12 @@ impl Component for Model {
     fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>, _root: &Self::Root) {
         match message {
             Input::Connect(address, connect) => {
-                service::dbus::bluez::Service::send(service::dbus::bluez::Input::Connect(
-                    address, connect,
-                ));
+                if let Err(e) = service::dbus::bluez::Service::send(
+                    service::dbus::bluez::Input::Connect(address.clone(), connect),
+                ) {
+                    let action = if connect { "connect" } else { "disconnect" };
+                    azalea_log::warning!("Failed to {action} bluetooth device {address}: {e}");
+                }
             }
             Input::Bluez(output) => match output {
                 service::dbus::bluez::Output::Connected(device_address, connected) => {
@@ -151,7 +163,14 @@ impl Component for Model {
                 }
             },
             Input::Power(on) => {
-                service::dbus::bluez::Service::send(service::dbus::bluez::Input::Power(on));
+                if let Err(e) =
+                    service::dbus::bluez::Service::send(service::dbus::bluez::Input::Power(on))
+                {
+                    azalea_log::warning!(
+                        "Failed to power bluetooth {}: {e}",
+                        if on { "on" } else { "off" }
+                    );
+                }
             }
         }
     }
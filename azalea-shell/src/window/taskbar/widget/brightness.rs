@@ -96,9 +96,9 @@ impl SimpleComponent for Model {
             Input::Scroll(delta) => {
                 let brightness = self.brightness - delta * 0.05;
                 if brightness >= 0. && brightness <= 1. {
-                    service::brightness::Service::send(
+                    drop(service::brightness::Service::send(
                         service::brightness::Input::SystemBrightness(brightness),
-                    );
+                    ));
                 }
             }
             Input::Brightness(output) => match output {
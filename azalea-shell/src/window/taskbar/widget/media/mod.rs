@@ -226,7 +226,7 @@ impl Component for Model {
         match message {
             Input::Select(name) => {
                 self.selected = Some(name.clone());
-                mpris::Service::send(mpris::Input::UpdateMetadata(name));
+                drop(mpris::Service::send(mpris::Input::UpdateMetadata(name)));
             }
             Input::Event(output) => {
                 if !self.players.contains_key(&output.name) {
@@ -273,11 +273,11 @@ impl Component for Model {
                 let Some(name) = self.selected.clone() else {
                     return;
                 };
-                mpris::Service::send(mpris::Input::Action(match action {
+                drop(mpris::Service::send(mpris::Input::Action(match action {
                     Action::Previous => mpris::Action::Previous(name),
                     Action::Next => mpris::Action::Next(name),
                     Action::PlayPause => mpris::Action::PlayPause(name),
-                }));
+                })));
             }
         }
     }
@@ -344,7 +344,9 @@ impl Model {
         });
 
         if let Some(name) = &self.selected {
-            mpris::Service::send(mpris::Input::UpdatePositionAndRate(name.clone()));
+            drop(mpris::Service::send(mpris::Input::UpdatePositionAndRate(
+                name.clone(),
+            )));
         }
     }
 
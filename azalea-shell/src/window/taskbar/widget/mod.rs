@@ -25,3 +25,12 @@ register_widgets!(
     StartMenu, startmenu::Model;
     Time, time::Model;
 );
+
+impl ConfigWrapper {
+    pub fn validate(&self) -> Vec<String> {
+        match self {
+            ConfigWrapper::Shortcut(config) => config.validate(),
+            _ => vec![],
+        }
+    }
+}
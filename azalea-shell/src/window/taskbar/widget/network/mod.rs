@@ -149,7 +149,9 @@ impl Component for Model {
                 .detach(),
         };
 
-        network_manager::Service::send(network_manager::Input::Update);
+        drop(network_manager::Service::send(
+            network_manager::Input::Update,
+        ));
 
         let devices_widget = model.devices_menu.widget();
         let connections_widget = model.connections_menu.widget();
@@ -187,7 +189,12 @@ impl Component for Model {
                 }
             },
             Input::Enable(on) => {
-                network_manager::Service::send(network_manager::Input::Enable(on));
+                if let Err(e) = network_manager::Service::send(network_manager::Input::Enable(on)) {
+                    azalea_log::warning!(
+                        "Failed to {} networking: {e}",
+                        if on { "enable" } else { "disable" }
+                    );
+                }
             }
         }
     }
@@ -90,10 +90,14 @@ impl Component for Model {
             ),
         };
 
-        let (tx, rx) = flume::bounded(1);
-        service::search::Service::send(service::search::Input::GetAllApplications(tx));
         sender.oneshot_command(async move {
-            let mut applications = rx.recv_async().await.unwrap();
+            let mut applications =
+                service::search::Service::request(service::search::Query::AllApplications)
+                    .await
+                    .unwrap_or_else(|e| {
+                        azalea_log::warning!("Failed to get applications: {e}");
+                        vec![]
+                    });
             applications.sort_by(|a, b| a.name.cmp(&b.name));
             CommandOutput::SetApplications(applications)
         });
@@ -15,6 +15,20 @@ crate::init! {
     }
 }
 
+impl Config {
+    pub fn validate(&self) -> Vec<String> {
+        let found = gio::AppInfo::all()
+            .iter()
+            .any(|app| app.id().is_some_and(|id| id == self.desktop_entry.as_str()));
+
+        if found {
+            vec![]
+        } else {
+            vec![format!("Unknown desktop entry {}", self.desktop_entry)]
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Input {
     Click,
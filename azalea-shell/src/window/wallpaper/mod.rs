@@ -1,23 +1,98 @@
 //! # Azalea wallpaper window
 
-use gtk::glib::object::Cast;
-use relm4::{Component, ComponentController, ComponentParts, ComponentSender, component};
+pub mod slideshow;
 
-use crate::component::image;
+use std::{cell::RefCell, collections::HashMap, path::Path, rc::Rc};
+
+use azalea_service::StaticHandler;
+use gtk::{cairo, gdk_pixbuf, prelude::*};
+use gtk4_layer_shell::LayerShell;
+use relm4::{Component, ComponentParts, ComponentSender, component};
+
+use crate::{component::image, service::theme};
 
 crate::init! {
     Model {
-        image: relm4::Controller<image::Model>,
+        config: Config,
+        window: gtk::Window,
+        area: gtk::DrawingArea,
+        frame: Rc<RefCell<Frame>>,
+        // Image or directory currently shown
+        source: Option<String>,
+        // Source was set at runtime, it's kept when the window moves to another monitor
+        overridden: bool,
+        // Image being loaded, older loads are discarded
+        loading: Option<String>,
+        slideshow: Vec<String>,
+        position: usize,
+        // Bumped whenever the source changes, so stale slideshow timers are ignored
+        generation: u64,
     }
 
     Config {
+        /// Image (path, url or base64) or a directory of images shown as a slideshow
         image: Option<String>,
+        /// Images by monitor connector, takes precedence over `image`
+        #[serde(default)]
+        monitors: HashMap<String, String>,
+        #[serde(default)]
+        fit: Fit,
+        #[serde(default)]
+        slideshow: slideshow::Config,
+        /// Crossfade duration between images in milliseconds, 0 disables it
+        #[serde(default = "default_crossfade")]
+        crossfade: u64,
     }
 }
 
+fn default_crossfade() -> u64 {
+    500
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+pub enum Fit {
+    /// Fill the monitor, cropping the image
+    #[default]
+    Cover,
+    /// Show the whole image, leaving empty bands
+    Contain,
+    /// Repeat the image at its original size
+    Tile,
+    /// Image at its original size, centered
+    Center,
+}
+
+/// What's drawn by the wallpaper, the current image fades in over the previous one
+pub struct Frame {
+    fit: Fit,
+    current: Option<gdk_pixbuf::Pixbuf>,
+    previous: Option<gdk_pixbuf::Pixbuf>,
+    progress: f64,
+    /// Bumped on every transition, so an interrupted one stops ticking
+    transition: u64,
+}
+
 #[derive(Debug)]
 pub enum Input {
+    /// Shows an image or a directory slideshow, replacing the configured one
     Update(String),
+    /// Window was mapped, it may have moved to another monitor
+    Mapped,
+}
+
+#[derive(Debug)]
+pub enum CommandOutput {
+    Loaded(String, Result<image::Decoded, String>),
+    NextImage(u64),
 }
 
 #[component(pub)]
@@ -25,48 +100,97 @@ impl Component for Model {
     type Init = Init;
     type Input = Input;
     type Output = ();
-    type CommandOutput = Input;
+    type CommandOutput = CommandOutput;
 
     view! {
         gtk::Window {
             #[local_ref]
-            image_widget -> gtk::Widget {},
+            area -> gtk::DrawingArea {
+                set_hexpand: true,
+                set_vexpand: true,
+            },
         },
     }
 
     fn init(
         init: Self::Init,
-        _root: Self::Root,
+        root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
+        let fallback = gdk_pixbuf::Pixbuf::from_read(std::io::Cursor::new(include_bytes!(
+            "../../../../assets/azalea-wallpaper.png"
+        )))
+        .ok();
+
+        let frame = Rc::new(RefCell::new(Frame {
+            fit: init.config.fit,
+            current: fallback,
+            previous: None,
+            progress: 1.,
+            transition: 0,
+        }));
+
+        let area = gtk::DrawingArea::new();
+        area.set_draw_func({
+            let frame = frame.clone();
+            move |_, cr, width, height| frame.borrow().draw(cr, width, height)
+        });
+
+        root.connect_map({
+            let sender = sender.clone();
+            move |_| sender.input(Input::Mapped)
+        });
+
+        register(&root, sender.input_sender().clone());
+
         let model = Model {
-            image: image::Model::builder()
-                .launch(image::Init {
-                    fallback: Some(
-                        gtk::gdk::Texture::from_bytes(&gtk::glib::Bytes::from_static(
-                            include_bytes!("../../../../assets/azalea-wallpaper.png"),
-                        ))
-                        .unwrap(),
-                    ),
-                    width: None,
-                    height: None,
-                })
-                .detach(),
+            config: init.config,
+            window: root.clone(),
+            area: area.clone(),
+            frame,
+            source: None,
+            overridden: false,
+            loading: None,
+            slideshow: vec![],
+            position: 0,
+            generation: 0,
         };
 
-        if let Some(image) = init.config.image {
-            sender.input(Input::Update(image));
-        }
-
-        let image_widget: &gtk::Widget = model.image.widget().upcast_ref();
+        let area = &model.area;
         let widgets = view_output!();
 
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>, _root: &Self::Root) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
         match message {
-            Input::Update(image) => self.image.emit(image::Input::LoadImage(image)),
+            Input::Update(source) => {
+                self.overridden = true;
+                self.show(source, &sender);
+            }
+            Input::Mapped => {
+                if self.overridden {
+                    return;
+                }
+
+                let connector = self
+                    .window
+                    .monitor()
+                    .and_then(|monitor| monitor.connector())
+                    .map(|connector| connector.to_string());
+
+                let source = connector
+                    .and_then(|connector| self.config.monitors.get(&connector))
+                    .or(self.config.image.as_ref())
+                    .cloned();
+
+                match source {
+                    Some(source) if self.source.as_ref() != Some(&source) => {
+                        self.show(source, &sender)
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 
@@ -77,7 +201,230 @@ impl Component for Model {
         _root: &Self::Root,
     ) {
         match message {
-            Input::Update(image) => sender.input(Input::Update(image)),
+            CommandOutput::Loaded(image, decoded) => {
+                if self.loading.as_ref() != Some(&image) {
+                    return;
+                }
+                self.loading = None;
+
+                match decoded {
+                    Ok(decoded) => {
+                        if let Err(e) = theme::Service::send(theme::Input::Wallpaper(image.clone()))
+                        {
+                            azalea_log::warning!(
+                                "Failed to update theme from wallpaper {image}: {e}"
+                            );
+                        }
+                        self.set_pixbuf(decoded.pixbuf());
+                    }
+                    Err(e) => azalea_log::warning!("Failed to load wallpaper {image}: {e}"),
+                }
+            }
+            CommandOutput::NextImage(generation) => {
+                if generation != self.generation || self.slideshow.is_empty() {
+                    return;
+                }
+
+                self.position = (self.position + 1) % self.slideshow.len();
+                if self.position == 0 && self.config.slideshow.shuffle {
+                    slideshow::shuffle(&mut self.slideshow);
+                }
+
+                self.load(self.slideshow[self.position].clone(), &sender);
+                self.schedule_next_image(&sender);
+            }
+        }
+    }
+}
+
+impl Model {
+    /// Shows an image, or starts a slideshow if `source` is a directory
+    fn show(&mut self, source: String, sender: &ComponentSender<Self>) {
+        self.generation += 1;
+        self.source = Some(source.clone());
+        self.slideshow.clear();
+        self.position = 0;
+
+        let path = Path::new(source.strip_prefix("file://").unwrap_or(&source));
+        if !path.is_dir() {
+            self.load(source, sender);
+            return;
+        }
+
+        self.slideshow = slideshow::images(path);
+        if self.config.slideshow.shuffle {
+            slideshow::shuffle(&mut self.slideshow);
+        }
+
+        match self.slideshow.first() {
+            Some(image) => {
+                self.load(image.clone(), sender);
+                self.schedule_next_image(sender);
+            }
+            None => azalea_log::warning!("No images found in wallpaper directory: {source}"),
+        }
+    }
+
+    fn load(&mut self, image: String, sender: &ComponentSender<Self>) {
+        self.loading = Some(image.clone());
+        // Decoded off the GTK thread, wallpapers are often 4K images
+        sender.oneshot_command(async move {
+            let decoded = match image::Model::load_image(&image).await {
+                Some(data) => image::Model::decode(data, None, None).await,
+                None => Err(String::from("Failed to fetch image")),
+            };
+            CommandOutput::Loaded(image, decoded)
+        });
+    }
+
+    fn schedule_next_image(&self, sender: &ComponentSender<Self>) {
+        let generation = self.generation;
+        let interval = std::time::Duration::from_secs(self.config.slideshow.interval.max(1));
+
+        sender.oneshot_command(async move {
+            tokio::time::sleep(interval).await;
+            CommandOutput::NextImage(generation)
+        });
+    }
+
+    /// Replaces the drawn image, crossfading from the previous one
+    fn set_pixbuf(&mut self, pixbuf: gdk_pixbuf::Pixbuf) {
+        let transition = {
+            let mut frame = self.frame.borrow_mut();
+            frame.previous = frame.current.replace(pixbuf);
+            frame.transition += 1;
+            frame.progress = 0.;
+            frame.transition
+        };
+
+        let clock = self.area.frame_clock();
+        let Some(start) = clock
+            .filter(|_| self.config.crossfade > 0)
+            .map(|c| c.frame_time())
+        else {
+            self.frame.borrow_mut().finish_transition();
+            self.area.queue_draw();
+            return;
+        };
+
+        // Frame times are in microseconds
+        let duration = self.config.crossfade as f64 * 1000.;
+        let frame = self.frame.clone();
+
+        self.area.add_tick_callback(move |area, clock| {
+            let mut frame = frame.borrow_mut();
+            if frame.transition != transition {
+                return gtk::glib::ControlFlow::Break;
+            }
+
+            frame.progress = ((clock.frame_time() - start) as f64 / duration).min(1.);
+            area.queue_draw();
+
+            if frame.progress < 1. {
+                gtk::glib::ControlFlow::Continue
+            } else {
+                frame.finish_transition();
+                gtk::glib::ControlFlow::Break
+            }
+        });
+    }
+}
+
+impl Frame {
+    fn finish_transition(&mut self) {
+        self.previous = None;
+        self.progress = 1.;
+    }
+
+    fn draw(&self, cr: &cairo::Context, width: i32, height: i32) {
+        // Kept opaque underneath, so the background never shows through mid fade
+        if let Some(previous) = &self.previous {
+            self.paint(cr, previous, width, height, 1.);
+        }
+        if let Some(current) = &self.current {
+            self.paint(cr, current, width, height, self.progress);
         }
     }
+
+    fn paint(
+        &self,
+        cr: &cairo::Context,
+        pixbuf: &gdk_pixbuf::Pixbuf,
+        width: i32,
+        height: i32,
+        alpha: f64,
+    ) {
+        let (width, height) = (width as f64, height as f64);
+        let (image_width, image_height) = (pixbuf.width() as f64, pixbuf.height() as f64);
+
+        drop(cr.save());
+
+        match self.fit {
+            Fit::Cover | Fit::Contain => {
+                let (horizontal, vertical) = (width / image_width, height / image_height);
+                let scale = match self.fit {
+                    Fit::Cover => horizontal.max(vertical),
+                    _ => horizontal.min(vertical),
+                };
+
+                cr.translate(
+                    (width - image_width * scale) / 2.,
+                    (height - image_height * scale) / 2.,
+                );
+                cr.scale(scale, scale);
+                cr.set_source_pixbuf(pixbuf, 0., 0.);
+            }
+            Fit::Tile => {
+                cr.set_source_pixbuf(pixbuf, 0., 0.);
+                cr.source().set_extend(cairo::Extend::Repeat);
+            }
+            Fit::Center => cr.set_source_pixbuf(
+                pixbuf,
+                ((width - image_width) / 2.).round(),
+                ((height - image_height) / 2.).round(),
+            ),
+        }
+
+        drop(cr.paint_with_alpha(alpha));
+        drop(cr.restore());
+    }
+}
+
+thread_local! {
+    /// Every wallpaper instance, so commands can reach them
+    static INSTANCES: RefCell<Vec<(gtk::glib::WeakRef<gtk::Window>, relm4::Sender<Input>)>> =
+        Default::default();
+}
+
+fn register(window: &gtk::Window, sender: relm4::Sender<Input>) {
+    INSTANCES.with_borrow_mut(|instances| instances.push((window.downgrade(), sender)));
+}
+
+/// Shows an image (or a directory slideshow) on every wallpaper, or only on the one attached to
+/// the given monitor connector
+///
+/// Returns how many wallpapers were updated
+pub fn set(image: String, monitor: Option<&str>) -> usize {
+    INSTANCES.with_borrow_mut(|instances| {
+        instances.retain(|(window, _)| window.upgrade().is_some());
+
+        let mut updated = 0;
+        for (window, sender) in instances.iter() {
+            let Some(window) = window.upgrade() else {
+                continue;
+            };
+
+            let connector = window.monitor().and_then(|m| m.connector());
+            if monitor.is_some_and(|monitor| {
+                connector.is_none_or(|connector| connector.as_str() != monitor)
+            }) {
+                continue;
+            }
+
+            if sender.send(Input::Update(image.clone())).is_ok() {
+                updated += 1;
+            }
+        }
+        updated
+    })
 }
@@ -0,0 +1,62 @@
+use std::{
+    hash::{BuildHasher, Hasher},
+    path::Path,
+};
+
+use gtk::gdk_pixbuf;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct Config {
+    /// Seconds between images
+    pub interval: u64,
+    /// Images are shown in a random order, reshuffled after each round
+    pub shuffle: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: 600,
+            shuffle: false,
+        }
+    }
+}
+
+/// Images inside a directory, sorted by name, only formats supported by gdk-pixbuf are kept
+pub fn images(directory: &Path) -> Vec<String> {
+    let extensions: Vec<String> = gdk_pixbuf::Pixbuf::formats()
+        .iter()
+        .flat_map(|format| format.extensions())
+        .map(|extension| extension.to_lowercase())
+        .collect();
+
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        azalea_log::warning!("Failed to read wallpaper directory: {:?}", directory);
+        return vec![];
+    };
+
+    let mut images: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extensions.contains(&extension.to_lowercase()))
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    images.sort();
+    images
+}
+
+/// Fisher-Yates shuffle, randomness comes from the std hasher seed
+pub fn shuffle(images: &mut [String]) {
+    let state = std::collections::hash_map::RandomState::new();
+
+    for i in (1..images.len()).rev() {
+        let mut hasher = state.build_hasher();
+        hasher.write_usize(i);
+        images.swap(i, hasher.finish() as usize % (i + 1));
+    }
+}
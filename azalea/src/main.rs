@@ -1,18 +1,20 @@
-use std::collections::HashMap;
+use std::{cell::Cell, collections::HashMap, sync::OnceLock};
 
 use azalea::{
     core::{
         app::{self},
-        config,
+        cli, config, event, style,
     },
-    shell::{self, icon, window::taskbar},
+    shell::{self, icon, service::theme, window::taskbar},
 };
 use azalea_core::{config::Config, monitor::Monitor};
+use azalea_service::{ListenerHandle, StaticHandler};
 use azalea_shell::window::wallpaper;
+use gtk::prelude::{Cast, CastNone, ListModelExt, WidgetExt};
 use relm4::{Component, ComponentController};
 
 // TODO: Macro to create Init based on list of widgets?
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema, Clone, Debug)]
 pub enum ConfigWrapper {
     Default,
     Taskbar(taskbar::Config),
@@ -25,11 +27,93 @@ pub enum WindowWrapper {
     Wallpaper(relm4::component::Controller<wallpaper::Model>),
 }
 
+#[derive(clap::Subcommand, serde::Serialize, serde::Deserialize, Debug)]
+pub enum Command {
+    /// Control media players
+    #[command(subcommand)]
+    Media(MediaCommand),
+
+    /// Set system volume (percentage), prefix it with + or - for relative changes
+    Volume {
+        #[arg(allow_hyphen_values = true)]
+        volume: String,
+    },
+
+    /// Control the wallpaper
+    #[command(subcommand)]
+    Wallpaper(WallpaperCommand),
+
+    /// Switch between light and dark mode, the system preference is followed by default
+    #[command(subcommand)]
+    ColorScheme(ColorSchemeCommand),
+
+    /// Inspect and control the services running in the daemon
+    #[command(subcommand)]
+    Services(ServicesCommand),
+}
+
+#[derive(clap::Subcommand, serde::Serialize, serde::Deserialize, Debug)]
+pub enum MediaCommand {
+    PlayPause { player: String },
+    Next { player: String },
+    Previous { player: String },
+}
+
+#[derive(clap::Subcommand, serde::Serialize, serde::Deserialize, Debug)]
+pub enum WallpaperCommand {
+    /// Show an image, or a slideshow if a directory is given
+    Set {
+        #[arg(value_parser = absolute_path)]
+        path: String,
+        /// Monitor connector, every wallpaper is updated if not given
+        #[arg(long)]
+        monitor: Option<String>,
+    },
+}
+
+/// Paths are resolved by the client, the daemon may run from another directory
+fn absolute_path(path: &str) -> Result<String, String> {
+    if path.contains("://") || path.starts_with("data:") {
+        return Ok(path.to_string());
+    }
+
+    std::path::absolute(path)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[derive(clap::Subcommand, serde::Serialize, serde::Deserialize, Debug)]
+pub enum ColorSchemeCommand {
+    Toggle,
+    Light,
+    Dark,
+    /// Follow the system preference again
+    System,
+}
+
+#[derive(clap::Subcommand, serde::Serialize, serde::Deserialize, Debug)]
+pub enum ServicesCommand {
+    /// Status, uptime, restarts, listeners and channel usage of every service
+    List,
+    /// Services are named like `audio` or `dbus::mpris`
+    Start {
+        name: String,
+    },
+    Stop {
+        name: String,
+    },
+    Restart {
+        name: String,
+    },
+}
+
 pub struct AzaleaAppExt {}
 
 impl app::AzaleaAppExt for AzaleaAppExt {
     type ConfigWrapper = ConfigWrapper;
+    type WidgetTemplate = taskbar::widget::ConfigWrapper;
     type WindowWrapper = WindowWrapper;
+    type Command = Command;
 
     fn create_window(init: &ConfigWrapper) -> WindowWrapper {
         match &init {
@@ -66,30 +150,252 @@ impl app::AzaleaAppExt for AzaleaAppExt {
             WindowWrapper::Wallpaper(controller) => controller.widget(),
         }
     }
+
+    fn activate(_app: &gtk::Application) {
+        use shell::service::dbus::appearance;
+
+        static THEME: OnceLock<ListenerHandle> = OnceLock::new();
+        static APPEARANCE: OnceLock<ListenerHandle> = OnceLock::new();
+
+        THEME.get_or_init(|| {
+            theme::Service::listen(|output| {
+                match output {
+                    theme::Output::Palette { palette, variant } => {
+                        style::set_variables(&palette.variables(variant))
+                    }
+                }
+                true
+            })
+        });
+
+        // Windows created later on get the class too
+        gtk::Window::toplevels().connect_items_changed(|toplevels, position, _, added| {
+            for window in (position..position + added)
+                .filter_map(|i| toplevels.item(i).and_downcast::<gtk::Window>())
+            {
+                set_variant_class(&window);
+            }
+        });
+
+        APPEARANCE.get_or_init(|| {
+            appearance::Service::listen(|output| {
+                match output {
+                    appearance::Output::Changed { variant, .. } => {
+                        drop(theme::Service::send(theme::Input::Variant(variant)));
+                        gtk::glib::MainContext::default().invoke(move || {
+                            VARIANT.set(variant);
+                            for window in gtk::Window::list_toplevels() {
+                                if let Some(window) = window.downcast_ref::<gtk::Window>() {
+                                    set_variant_class(window);
+                                }
+                            }
+                        });
+                    }
+                }
+                true
+            })
+        });
+    }
+
+    fn validate(config: &ConfigWrapper) -> Vec<String> {
+        match config {
+            ConfigWrapper::Taskbar(config) => config.validate(),
+            _ => vec![],
+        }
+    }
+
+    fn handle_command(command: Command) -> cli::Response {
+        match command {
+            Command::Media(command) => {
+                use shell::service::dbus::mpris::{self, Action, OwnedBusName};
+
+                let player = match &command {
+                    MediaCommand::PlayPause { player }
+                    | MediaCommand::Next { player }
+                    | MediaCommand::Previous { player } => player,
+                };
+
+                let player = if player.contains('.') {
+                    player.clone()
+                } else {
+                    format!("org.mpris.MediaPlayer2.{player}")
+                };
+
+                let Ok(bus_name) = OwnedBusName::try_from(player.as_str()) else {
+                    return cli::Response::Error(format!("Invalid player name: {player}"));
+                };
+
+                let sent = mpris::Service::send(mpris::Input::Action(match command {
+                    MediaCommand::PlayPause { .. } => Action::PlayPause(bus_name),
+                    MediaCommand::Next { .. } => Action::Next(bus_name),
+                    MediaCommand::Previous { .. } => Action::Previous(bus_name),
+                }));
+
+                if let Err(e) = sent {
+                    return cli::Response::Error(e.to_string());
+                }
+            }
+            Command::Volume { volume } => {
+                use shell::service::audio;
+
+                let (sign, value) = match (volume.strip_prefix('+'), volume.strip_prefix('-')) {
+                    (Some(value), _) => (Some(1.), value),
+                    (_, Some(value)) => (Some(-1.), value),
+                    _ => (None, volume.as_str()),
+                };
+
+                let Ok(value) = value.parse::<f64>() else {
+                    return cli::Response::Error(format!("Invalid volume: {volume}"));
+                };
+
+                let sent = audio::Service::send(match sign {
+                    Some(sign) => audio::Input::AdjustSystemVolume(sign * value / 100.),
+                    None => audio::Input::SystemVolume((value / 100.).clamp(0., 1.)),
+                });
+
+                if let Err(e) = sent {
+                    return cli::Response::Error(e.to_string());
+                }
+            }
+            Command::Wallpaper(WallpaperCommand::Set { path, monitor }) => {
+                if wallpaper::set(path, monitor.as_deref()) == 0 {
+                    return cli::Response::Error(match monitor {
+                        Some(monitor) => format!("No wallpaper on monitor: {monitor}"),
+                        None => format!("No wallpaper window is open"),
+                    });
+                }
+            }
+            Command::ColorScheme(command) => {
+                use shell::service::dbus::appearance::{self, Variant};
+
+                let sent = appearance::Service::send(match command {
+                    ColorSchemeCommand::Toggle => appearance::Input::Toggle,
+                    ColorSchemeCommand::Light => appearance::Input::Set(Some(Variant::Light)),
+                    ColorSchemeCommand::Dark => appearance::Input::Set(Some(Variant::Dark)),
+                    ColorSchemeCommand::System => appearance::Input::Set(None),
+                });
+
+                if let Err(e) = sent {
+                    return cli::Response::Error(e.to_string());
+                }
+            }
+            Command::Services(command) => {
+                use azalea_service::registry::{self, Entry};
+
+                let (name, action): (String, fn(Entry) -> fn()) = match command {
+                    ServicesCommand::List => {
+                        return cli::Response::Success(services_to_string());
+                    }
+                    ServicesCommand::Start { name } => (name, |entry| entry.start),
+                    ServicesCommand::Stop { name } => (name, |entry| entry.stop),
+                    ServicesCommand::Restart { name } => (name, |entry| entry.restart),
+                };
+
+                match registry::find(&name) {
+                    Ok(entry) => action(entry)(),
+                    Err(e) => return cli::Response::Error(e),
+                }
+            }
+        }
+
+        cli::Response::Success(format!("Ok"))
+    }
+}
+
+fn services_to_string() -> String {
+    use azalea_service::{Channel, Status, registry};
+
+    let channel = |channel: Channel| match channel.capacity {
+        Some(capacity) => format!("{}/{capacity}", channel.len),
+        None => format!("{}", channel.len),
+    };
+
+    registry::entries()
+        .iter()
+        .map(|entry| {
+            let health = (entry.health)();
+            let status = match &health.status {
+                Status::Starting => format!("starting"),
+                Status::Started => format!("started"),
+                Status::Stopped => format!("stopped"),
+                Status::Failed(reason) => format!("failed ({reason})"),
+            };
+            let uptime = match health.uptime {
+                Some(uptime) => format!("{}s", uptime.as_secs()),
+                None => format!("-"),
+            };
+
+            format!(
+                "{}: {status}, uptime {uptime}, restarts {}, listeners {}, input {}, output {}, lagged {}, dropped {}",
+                entry.short_name(),
+                health.restarts,
+                health.listeners,
+                channel(health.input),
+                channel(health.output),
+                health.lagged,
+                health.dropped,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+thread_local! {
+    static VARIANT: Cell<theme::Variant> = Cell::new(Default::default());
+}
+
+/// Adds a `light` or `dark` class to the window, following the current color scheme
+fn set_variant_class(window: &gtk::Window) {
+    let (class, other) = match VARIANT.get() {
+        theme::Variant::Light => ("light", "dark"),
+        theme::Variant::Dark => ("dark", "light"),
+    };
+    window.remove_css_class(other);
+    window.add_css_class(class);
 }
 
 fn main() {
     icon::init();
 
+    azalea::service::observe_outputs(|service| {
+        if event::has_subscribers(event::Topic::Service) {
+            event::publish(event::Event::ServiceOutput {
+                service: service.to_string(),
+            });
+        }
+    });
+
     let windows = HashMap::from([
         (
             format!("wallpaper"),
             config::window::Config {
-                config: ConfigWrapper::Wallpaper(wallpaper::Config { image: None }),
+                config: ConfigWrapper::Wallpaper(wallpaper::Config {
+                    image: None,
+                    monitors: Default::default(),
+                    fit: wallpaper::Fit::Cover,
+                    slideshow: Default::default(),
+                    crossfade: 500,
+                }),
 
                 layer_shell: Some({
-                    use config::layer_shell::{Anchor, Config, ExclusiveZone, Layer};
+                    use config::layer_shell::{Anchor, Config, ExclusiveZone, KeyboardMode, Layer};
 
                     Config {
                         namespace: format!("wallpaper"),
                         layer: Layer::Background,
                         anchors: vec![Anchor::Left, Anchor::Right, Anchor::Bottom, Anchor::Top],
                         exclusive_zone: ExclusiveZone::Ignore,
+                        keyboard_mode: KeyboardMode::None,
+                        margins: Default::default(),
+                        width: None,
+                        height: None,
                     }
                 }),
 
                 lazy: false,
 
+                hidden: false,
+
                 monitor: Monitor::All,
             },
         ),
@@ -133,18 +439,24 @@ fn main() {
                 }),
 
                 layer_shell: Some({
-                    use config::layer_shell::{Anchor, Config, ExclusiveZone, Layer};
+                    use config::layer_shell::{Anchor, Config, ExclusiveZone, KeyboardMode, Layer};
 
                     Config {
                         namespace: format!("taskbar"),
                         layer: Layer::Top,
                         anchors: vec![Anchor::Left, Anchor::Right, Anchor::Top],
                         exclusive_zone: ExclusiveZone::Auto,
+                        keyboard_mode: KeyboardMode::OnDemand,
+                        margins: Default::default(),
+                        width: None,
+                        height: None,
                     }
                 }),
 
                 lazy: false,
 
+                hidden: false,
+
                 monitor: Monitor::All,
             },
         ),
@@ -190,22 +502,35 @@ fn main() {
                 }),
 
                 layer_shell: Some({
-                    use config::layer_shell::{Anchor, Config, ExclusiveZone, Layer};
+                    use config::layer_shell::{Anchor, Config, ExclusiveZone, KeyboardMode, Layer};
 
                     Config {
                         namespace: format!("taskbar"),
                         layer: Layer::Top,
                         anchors: vec![Anchor::Left, Anchor::Right, Anchor::Bottom],
                         exclusive_zone: ExclusiveZone::Auto,
+                        keyboard_mode: KeyboardMode::OnDemand,
+                        margins: Default::default(),
+                        width: None,
+                        height: None,
                     }
                 }),
 
                 lazy: false,
 
+                hidden: false,
+
                 monitor: Monitor::All,
             },
         ),
     ]);
 
-    app::AzaleaApp::<AzaleaAppExt>::new(Config { windows }).run();
+    app::AzaleaApp::<AzaleaAppExt>::new(Config {
+        include: vec![],
+        templates: Default::default(),
+        windows,
+        layouts: Default::default(),
+        layout: None,
+    })
+    .run();
 }